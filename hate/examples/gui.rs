@@ -171,6 +171,7 @@ impl Screen for GuiTest {
         match event {
             Event::Click { pos } => self.handle_event_click(context, pos),
             Event::Resize { aspect_ratio } => self.gui.resize(aspect_ratio),
+            _ => {}
         }
         self.handle_commands(context);
     }
@@ -0,0 +1,59 @@
+//! Drives a screen without a window and checks what it draws.
+
+extern crate cgmath;
+extern crate hate;
+
+use cgmath::vec2;
+use hate::{Context, Event, Scene, Screen, Sprite, Time};
+use hate::geom::{Point, Size};
+use hate::scene::{action, Layer};
+use hate::gui;
+
+struct MovingTextScreen {
+    scene: Scene,
+}
+
+impl MovingTextScreen {
+    fn new(context: &mut Context) -> Self {
+        let layer = Layer::new();
+        let mut scene = Scene::new(vec![layer.clone()]);
+        let sprite: Sprite = gui::text_sprite(context, "headless", 0.2);
+        let delta = Point(vec2(1.0, 0.0));
+        scene.add_action(Box::new(action::Sequence::new(vec![
+            Box::new(action::Show::new(&layer, &sprite)),
+            Box::new(action::MoveBy::new(&sprite, delta, Time(1.0))),
+        ])));
+        Self { scene }
+    }
+}
+
+impl Screen for MovingTextScreen {
+    fn tick(&mut self, context: &mut Context, dtime: Time) {
+        self.scene.tick(dtime);
+        self.scene.draw(context);
+    }
+
+    fn handle_event(&mut self, _: &mut Context, _: Event) {}
+}
+
+fn first_vertex_x(context: &Context) -> f32 {
+    let draw_calls = context.draw_calls();
+    assert_eq!(draw_calls.len(), 1);
+    draw_calls[0].transformed_vertices()[0].0.x
+}
+
+pub fn main() {
+    let settings = hate::Settings::default();
+    let win_size = Size { w: 640, h: 480 };
+    let mut visualizer = hate::Visualizer::new_headless(settings, win_size);
+    let screen = MovingTextScreen::new(visualizer.context_mut());
+    visualizer.push_screen(Box::new(screen));
+    visualizer.step(Time(0.0));
+    let start_x = first_vertex_x(visualizer.context());
+    for _ in 0..10 {
+        visualizer.step(Time(0.1));
+    }
+    let end_x = first_vertex_x(visualizer.context());
+    assert!(end_x > start_x, "the sprite must move to the right");
+    println!("OK: the sprite moved from {} to {}", start_x, end_x);
+}
@@ -0,0 +1,305 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use settings::Settings;
+use context::Context;
+use fs;
+
+#[cfg(all(feature = "audio", not(target_os = "android")))]
+use std::io::Cursor;
+
+#[cfg(all(feature = "audio", not(target_os = "android")))]
+use rodio::{self, Source};
+
+/// Encoded OGG or WAV data, decoded on every play
+#[derive(Clone)]
+struct SoundData(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for SoundData {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// A loaded sound effect or music track. Cheap to clone.
+#[derive(Clone)]
+pub struct Sound {
+    data: SoundData,
+}
+
+impl Sound {
+    /// Loads a sound with caching. Panics if the file can't be loaded or decoded.
+    pub fn from_path<P: AsRef<Path>>(context: &mut Context, path: P) -> Self {
+        let path = path.as_ref();
+        match Self::try_from_path(context, path) {
+            Some(sound) => sound,
+            None => panic!("Can't load sound '{}'", path.display()),
+        }
+    }
+
+    /// Same as `from_path`, but returns `None` if the file can't be loaded or decoded.
+    pub fn try_from_path<P: AsRef<Path>>(context: &mut Context, path: P) -> Option<Self> {
+        context.audio().load(path.as_ref())
+    }
+
+    fn from_data(data: Vec<u8>) -> Option<Self> {
+        let sound = Sound {
+            data: SoundData(Arc::new(data)),
+        };
+        if sound.is_decodable() {
+            Some(sound)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(all(feature = "audio", not(target_os = "android")))]
+    fn decoder(&self) -> rodio::Decoder<Cursor<SoundData>> {
+        rodio::Decoder::new(Cursor::new(self.data.clone())).unwrap()
+    }
+
+    #[cfg(all(feature = "audio", not(target_os = "android")))]
+    fn is_decodable(&self) -> bool {
+        rodio::Decoder::new(Cursor::new(self.data.clone())).is_ok()
+    }
+
+    #[cfg(any(not(feature = "audio"), target_os = "android"))]
+    fn is_decodable(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for Sound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sound({} bytes)", self.data.0.len())
+    }
+}
+
+#[cfg(all(feature = "audio", not(target_os = "android")))]
+enum Output {
+    Device {
+        device: rodio::Device,
+        music: Option<rodio::Sink>,
+    },
+
+    /// No audio device: everything is silently ignored
+    Null,
+}
+
+#[cfg(any(not(feature = "audio"), target_os = "android"))]
+enum Output {
+    Null,
+}
+
+impl Output {
+    #[cfg(all(feature = "audio", not(target_os = "android")))]
+    fn new() -> Self {
+        match rodio::default_output_device() {
+            Some(device) => Output::Device {
+                device,
+                music: None,
+            },
+            None => {
+                warn!("Can't find an audio device, the sound is off");
+                Output::Null
+            }
+        }
+    }
+
+    #[cfg(any(not(feature = "audio"), target_os = "android"))]
+    fn new() -> Self {
+        // TODO: android audio
+        warn!("No audio support, the sound is off");
+        Output::Null
+    }
+
+    #[cfg(all(feature = "audio", not(target_os = "android")))]
+    fn play(&mut self, sound: &Sound, volume: f32) {
+        if let Output::Device { ref device, .. } = *self {
+            let mut sink = rodio::Sink::new(device);
+            sink.set_volume(volume);
+            sink.append(sound.decoder());
+            sink.detach();
+        }
+    }
+
+    #[cfg(any(not(feature = "audio"), target_os = "android"))]
+    fn play(&mut self, _: &Sound, _: f32) {}
+
+    #[cfg(all(feature = "audio", not(target_os = "android")))]
+    fn play_music(&mut self, sound: &Sound, volume: f32) {
+        if let Output::Device {
+            ref device,
+            ref mut music,
+        } = *self
+        {
+            if let Some(old_music) = music.take() {
+                old_music.stop();
+            }
+            let mut sink = rodio::Sink::new(device);
+            sink.set_volume(volume);
+            sink.append(sound.decoder().repeat_infinite());
+            *music = Some(sink);
+        }
+    }
+
+    #[cfg(any(not(feature = "audio"), target_os = "android"))]
+    fn play_music(&mut self, _: &Sound, _: f32) {}
+
+    #[cfg(all(feature = "audio", not(target_os = "android")))]
+    fn stop_music(&mut self) {
+        if let Output::Device { ref mut music, .. } = *self {
+            if let Some(music) = music.take() {
+                music.stop();
+            }
+        }
+    }
+
+    #[cfg(any(not(feature = "audio"), target_os = "android"))]
+    fn stop_music(&mut self) {}
+
+    #[cfg(all(feature = "audio", not(target_os = "android")))]
+    fn set_music_volume(&mut self, volume: f32) {
+        if let Output::Device {
+            music: Some(ref mut music),
+            ..
+        } = *self
+        {
+            music.set_volume(volume);
+        }
+    }
+
+    #[cfg(any(not(feature = "audio"), target_os = "android"))]
+    fn set_music_volume(&mut self, _: f32) {}
+
+    fn is_null(&self) -> bool {
+        match *self {
+            Output::Null => true,
+            #[cfg(all(feature = "audio", not(target_os = "android")))]
+            Output::Device { .. } => false,
+        }
+    }
+}
+
+struct AudioData {
+    output: Output,
+    sound_volume: f32,
+    music_volume: f32,
+    is_muted: bool,
+    cache: HashMap<PathBuf, Sound>,
+}
+
+impl AudioData {
+    fn music_volume(&self) -> f32 {
+        if self.is_muted {
+            0.0
+        } else {
+            self.music_volume
+        }
+    }
+}
+
+/// A shared handle: the context and the sound actions can hold clones of it.
+///
+/// Falls back to a null output that plays nothing if there's no audio device.
+#[derive(Clone)]
+pub struct Audio {
+    data: Rc<RefCell<AudioData>>,
+}
+
+impl Audio {
+    pub(crate) fn new(settings: &Settings) -> Self {
+        Self::with_output(settings, Output::new())
+    }
+
+    /// Never touches the audio devices
+    pub(crate) fn new_null(settings: &Settings) -> Self {
+        Self::with_output(settings, Output::Null)
+    }
+
+    fn with_output(settings: &Settings, output: Output) -> Self {
+        let data = AudioData {
+            output,
+            sound_volume: settings.sound_volume,
+            music_volume: settings.music_volume,
+            is_muted: settings.mute,
+            cache: HashMap::new(),
+        };
+        Self {
+            data: Rc::new(RefCell::new(data)),
+        }
+    }
+
+    fn load(&self, path: &Path) -> Option<Sound> {
+        if let Some(sound) = self.data.borrow().cache.get(path) {
+            return Some(sound.clone());
+        }
+        let sound = Sound::from_data(fs::try_load(path)?)?;
+        let mut data = self.data.borrow_mut();
+        data.cache.insert(path.to_path_buf(), sound.clone());
+        Some(sound)
+    }
+
+    /// `false` if the sound goes nowhere
+    pub fn has_device(&self) -> bool {
+        !self.data.borrow().output.is_null()
+    }
+
+    /// Plays a one-shot sound effect
+    pub fn play(&mut self, sound: &Sound) {
+        let mut data = self.data.borrow_mut();
+        if data.is_muted {
+            return;
+        }
+        let volume = data.sound_volume;
+        data.output.play(sound, volume);
+    }
+
+    /// Loops the music track, replacing the current one
+    pub fn play_music(&mut self, sound: &Sound) {
+        let mut data = self.data.borrow_mut();
+        let volume = data.music_volume();
+        data.output.play_music(sound, volume);
+    }
+
+    pub fn stop_music(&mut self) {
+        self.data.borrow_mut().output.stop_music();
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.data.borrow().is_muted
+    }
+
+    pub fn set_muted(&mut self, is_muted: bool) {
+        let mut data = self.data.borrow_mut();
+        data.is_muted = is_muted;
+        let volume = data.music_volume();
+        data.output.set_music_volume(volume);
+    }
+
+    pub fn set_sound_volume(&mut self, volume: f32) {
+        self.data.borrow_mut().sound_volume = volume;
+    }
+
+    pub fn set_music_volume(&mut self, volume: f32) {
+        let mut data = self.data.borrow_mut();
+        data.music_volume = volume;
+        let volume = data.music_volume();
+        data.output.set_music_volume(volume);
+    }
+}
+
+impl fmt::Debug for Audio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let data = self.data.borrow();
+        f.debug_struct("Audio")
+            .field("has_device", &!data.output.is_null())
+            .field("sound_volume", &data.sound_volume)
+            .field("music_volume", &data.music_volume)
+            .field("is_muted", &data.is_muted)
+            .finish()
+    }
+}
@@ -0,0 +1,212 @@
+use cgmath::{Matrix4, SquareMatrix};
+use glutin::{self, Api, GlContext};
+use gfx::traits::{Device, Factory, FactoryExt};
+use gfx::handle::{Program, ShaderResourceView};
+use gfx;
+use gfx_device_gl;
+use gfx_window_glutin;
+use backend::Backend;
+use geom::Size;
+use mesh::{Mesh, RawMesh, VertexIndex};
+use pipeline::{pipe, ColorFormat, Vertex};
+use texture::RawTexture;
+
+fn shader_version_string(api: Api) -> String {
+    match api {
+        Api::OpenGl => "#version 120\n".into(),
+        Api::OpenGlEs | Api::WebGl => "#version 100\n".into(),
+    }
+}
+
+fn vertex_shader(api: Api) -> String {
+    let shader = r#"
+        uniform mat4 u_ModelViewProj;
+        attribute vec2 a_Pos;
+        attribute vec2 a_Uv;
+        varying vec2 v_Uv;
+
+        void main() {
+            v_Uv = a_Uv;
+            gl_Position = u_ModelViewProj * vec4(a_Pos, 0.0, 1.0);
+        }
+    "#;
+    shader_version_string(api) + shader
+}
+
+fn fragment_shader(api: Api) -> String {
+    let mut header = shader_version_string(api);
+    if api == Api::OpenGlEs || api == Api::WebGl {
+        header += "precision mediump float;\n";
+    }
+    let shader = r#"
+        uniform vec4 u_Basic_color;
+        uniform sampler2D t_Tex;
+        varying vec2 v_Uv;
+
+        void main() {
+            gl_FragColor = u_Basic_color * texture2D(t_Tex, v_Uv);
+        }
+    "#;
+    header + shader
+}
+
+fn new_shader(
+    window: &glutin::GlWindow,
+    factory: &mut gfx_device_gl::Factory,
+) -> Program<gfx_device_gl::Resources> {
+    let api = window.get_api();
+    let vs = vertex_shader(api);
+    let fs = fragment_shader(api);
+    factory.link_program(vs.as_bytes(), fs.as_bytes()).unwrap()
+}
+
+fn new_pso(
+    factory: &mut gfx_device_gl::Factory,
+    program: &Program<gfx_device_gl::Resources>,
+    primitive: gfx::Primitive,
+) -> gfx::PipelineState<gfx_device_gl::Resources, pipe::Meta> {
+    let rasterizer = gfx::state::Rasterizer::new_fill();
+    let pso = factory.create_pipeline_from_program(program, primitive, rasterizer, pipe::new());
+    pso.unwrap()
+}
+
+fn gl_version() -> glutin::GlRequest {
+    glutin::GlRequest::GlThenGles {
+        opengles_version: (2, 0),
+        opengl_version: (2, 1),
+    }
+}
+
+fn load_texture_raw(
+    factory: &mut gfx_device_gl::Factory,
+    size: Size<i32>,
+    data: &[u8],
+) -> ShaderResourceView<gfx_device_gl::Resources, [f32; 4]> {
+    let kind = gfx::texture::Kind::D2(
+        size.w as gfx::texture::Size,
+        size.h as gfx::texture::Size,
+        gfx::texture::AaMode::Single,
+    );
+    let (_, view) = factory
+        .create_texture_immutable_u8::<ColorFormat>(kind, &[data])
+        .unwrap();
+    view
+}
+
+// TODO: use gfx-rs generics, not gfx_device_gl types
+pub struct GlBackend {
+    events_loop: glutin::EventsLoop,
+    window: glutin::GlWindow,
+    device: gfx_device_gl::Device,
+    encoder: gfx::Encoder<gfx_device_gl::Resources, gfx_device_gl::CommandBuffer>,
+    data: pipe::Data<gfx_device_gl::Resources>,
+    pso: gfx::PipelineState<gfx_device_gl::Resources, pipe::Meta>,
+    factory: gfx_device_gl::Factory,
+}
+
+impl GlBackend {
+    pub fn new() -> Self {
+        let window_builder = glutin::WindowBuilder::new().with_title("Zemeroth".to_string());
+        let context_builder = glutin::ContextBuilder::new()
+            .with_gl(gl_version())
+            .with_pixel_format(24, 8);
+        let events_loop = glutin::EventsLoop::new();
+        let (window, device, mut factory, out, out_depth) =
+            gfx_window_glutin::init(window_builder, context_builder, &events_loop);
+        let encoder = factory.create_command_buffer().into();
+        let program = new_shader(&window, &mut factory);
+        let primitive = gfx::Primitive::TriangleList;
+        let pso = new_pso(&mut factory, &program, primitive);
+        let sampler = factory.create_sampler_linear();
+        let fake_texture = load_texture_raw(&mut factory, Size { w: 2, h: 2 }, &[0; 16]);
+        let fake_mesh = [];
+        let data = pipe::Data {
+            basic_color: [1.0, 1.0, 1.0, 1.0],
+            vbuf: factory.create_vertex_buffer(&fake_mesh),
+            texture: (fake_texture, sampler),
+            out,
+            out_depth,
+            mvp: Matrix4::identity().into(),
+        };
+        Self {
+            events_loop,
+            window,
+            device,
+            encoder,
+            data,
+            pso,
+            factory,
+        }
+    }
+}
+
+impl Backend for GlBackend {
+    fn win_size(&self) -> Size<i32> {
+        let (w, h) = self.window.get_inner_size().unwrap();
+        Size {
+            w: w as i32,
+            h: h as i32,
+        }
+    }
+
+    fn hidpi_factor(&self) -> f32 {
+        self.window.hidpi_factor()
+    }
+
+    fn poll_events(&mut self) -> Vec<glutin::WindowEvent> {
+        let mut events = Vec::new();
+        self.events_loop.poll_events(|e| {
+            if let glutin::Event::WindowEvent { event, .. } = e {
+                events.push(event);
+            }
+        });
+        events
+    }
+
+    fn resize(&mut self, _: Size<i32>) {
+        gfx_window_glutin::update_views(&self.window, &mut self.data.out, &mut self.data.out_depth);
+    }
+
+    fn create_texture(&mut self, size: Size<i32>, data: &[u8]) -> RawTexture {
+        RawTexture::Gl(load_texture_raw(&mut self.factory, size, data))
+    }
+
+    fn create_mesh(&mut self, vertices: &[Vertex], indices: &[VertexIndex]) -> RawMesh {
+        let (vertex_buffer, slice) = self.factory
+            .create_vertex_buffer_with_slice(vertices, indices);
+        RawMesh::Gl {
+            slice,
+            vertex_buffer,
+        }
+    }
+
+    fn clear(&mut self, color: [f32; 4]) {
+        self.encoder.clear(&self.data.out, color);
+        self.encoder.clear_depth(&self.data.out_depth, 1.0);
+    }
+
+    fn draw_mesh(&mut self, mvp: Matrix4<f32>, color: [f32; 4], mesh: &Mesh) {
+        let (slice, vertex_buffer) = match *mesh.raw() {
+            RawMesh::Gl {
+                ref slice,
+                ref vertex_buffer,
+            } => (slice, vertex_buffer),
+            RawMesh::Headless { .. } => panic!("The mesh wasn't created by this backend"),
+        };
+        let texture = match mesh.texture().raw {
+            RawTexture::Gl(ref view) => view,
+            RawTexture::Headless => panic!("The texture wasn't created by this backend"),
+        };
+        self.data.basic_color = color;
+        self.data.mvp = mvp.into();
+        self.data.texture.0 = texture.clone();
+        self.data.vbuf = vertex_buffer.clone();
+        self.encoder.draw(slice, &self.pso, &self.data);
+    }
+
+    fn flush(&mut self) {
+        self.encoder.flush(&mut self.device);
+        self.window.swap_buffers().expect("Can`t swap buffers");
+        self.device.cleanup();
+    }
+}
@@ -0,0 +1,76 @@
+use cgmath::Matrix4;
+use glutin;
+use backend::{Backend, DrawCall};
+use geom::{Point, Size};
+use mesh::{Mesh, RawMesh, VertexIndex};
+use pipeline::Vertex;
+use texture::RawTexture;
+
+/// Draws nothing, only records the draw calls of the current frame.
+#[derive(Clone, Debug)]
+pub struct HeadlessBackend {
+    win_size: Size<i32>,
+    draw_calls: Vec<DrawCall>,
+}
+
+impl HeadlessBackend {
+    pub fn new(win_size: Size<i32>) -> Self {
+        Self {
+            win_size,
+            draw_calls: Vec::new(),
+        }
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn win_size(&self) -> Size<i32> {
+        self.win_size
+    }
+
+    fn hidpi_factor(&self) -> f32 {
+        1.0
+    }
+
+    fn poll_events(&mut self) -> Vec<glutin::WindowEvent> {
+        Vec::new()
+    }
+
+    fn resize(&mut self, win_size: Size<i32>) {
+        self.win_size = win_size;
+    }
+
+    fn create_texture(&mut self, _: Size<i32>, _: &[u8]) -> RawTexture {
+        RawTexture::Headless
+    }
+
+    fn create_mesh(&mut self, vertices: &[Vertex], indices: &[VertexIndex]) -> RawMesh {
+        let vertices = indices
+            .iter()
+            .map(|&i| Point(vertices[i as usize].pos.into()))
+            .collect();
+        RawMesh::Headless { vertices }
+    }
+
+    fn clear(&mut self, _: [f32; 4]) {
+        self.draw_calls.clear();
+    }
+
+    fn draw_mesh(&mut self, mvp: Matrix4<f32>, color: [f32; 4], mesh: &Mesh) {
+        let vertices = match *mesh.raw() {
+            RawMesh::Headless { ref vertices } => vertices.clone(),
+            RawMesh::Gl { .. } => panic!("The mesh wasn't created by this backend"),
+        };
+        self.draw_calls.push(DrawCall {
+            mvp,
+            color,
+            texture_size: mesh.texture().size,
+            vertices,
+        });
+    }
+
+    fn flush(&mut self) {}
+
+    fn draw_calls(&self) -> &[DrawCall] {
+        &self.draw_calls
+    }
+}
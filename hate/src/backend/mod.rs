@@ -0,0 +1,72 @@
+//! Renderers and windows.
+//!
+//! - `GlBackend` - a glutin window with an OpenGL (ES) context;
+//! - `HeadlessBackend` - no window at all, only records the draw calls,
+//!   so screens can be ticked in tests and on GPU-less machines.
+
+use cgmath::{Matrix4, Vector4};
+use glutin;
+use geom::{Point, Size};
+use mesh::{Mesh, RawMesh, VertexIndex};
+use pipeline::Vertex;
+use texture::RawTexture;
+
+mod gl;
+mod headless;
+
+pub use self::gl::GlBackend;
+pub use self::headless::HeadlessBackend;
+
+pub trait Backend {
+    fn win_size(&self) -> Size<i32>;
+
+    fn hidpi_factor(&self) -> f32;
+
+    /// Window events since the last call
+    fn poll_events(&mut self) -> Vec<glutin::WindowEvent>;
+
+    /// Called when the window is resized.
+    fn resize(&mut self, win_size: Size<i32>);
+
+    /// `data` is in the RGBA8 format.
+    fn create_texture(&mut self, size: Size<i32>, data: &[u8]) -> RawTexture;
+
+    fn create_mesh(&mut self, vertices: &[Vertex], indices: &[VertexIndex]) -> RawMesh;
+
+    fn clear(&mut self, color: [f32; 4]);
+
+    fn draw_mesh(&mut self, mvp: Matrix4<f32>, color: [f32; 4], mesh: &Mesh);
+
+    /// Presents the frame.
+    fn flush(&mut self);
+
+    /// Draw calls of the current frame if the backend records them.
+    fn draw_calls(&self) -> &[DrawCall] {
+        &[]
+    }
+}
+
+/// A recorded `draw_mesh` call
+#[derive(Clone, Debug)]
+pub struct DrawCall {
+    pub mvp: Matrix4<f32>,
+    pub color: [f32; 4],
+    pub texture_size: Size<i32>,
+
+    /// Triangles' vertices in the mesh's coordinates
+    pub vertices: Vec<Point>,
+}
+
+impl DrawCall {
+    /// Triangles' vertices in the normalized device coordinates
+    pub fn transformed_vertices(&self) -> Vec<Point> {
+        let mvp = self.mvp;
+        self.vertices
+            .iter()
+            .map(|p| {
+                let v = mvp * Vector4::new(p.0.x, p.0.y, 0.0, 1.0);
+                Point(v.truncate().truncate())
+            })
+            .collect()
+    }
+}
@@ -2,84 +2,22 @@ use std::collections::HashMap;
 use std::sync::mpsc::Sender;
 use std::path::{Path, PathBuf};
 use std::time;
-use cgmath::{self, InnerSpace, Matrix4, SquareMatrix, Vector2, Zero};
-use glutin::{self, Api, GlContext, MouseButton};
+use cgmath::{self, InnerSpace, Matrix4, Vector2, Zero};
+use glutin::{self, MouseButton};
 use glutin::ElementState::{Pressed, Released};
 use rusttype;
-use gfx::traits::{Device, FactoryExt};
-use gfx::handle::Program;
-use gfx;
-use gfx_device_gl;
-use gfx_window_glutin;
 use settings::Settings;
 use time::Time;
-use event::Event;
+use event::{Event, Key};
 use screen;
 use fs;
 use geom::{Point, Size};
-use pipeline::pipe;
 use mesh::Mesh;
 use texture::{self, Texture};
 use text;
-
-fn shader_version_string(api: Api) -> String {
-    match api {
-        Api::OpenGl => "#version 120\n".into(),
-        Api::OpenGlEs | Api::WebGl => "#version 100\n".into(),
-    }
-}
-
-fn vertex_shader(api: Api) -> String {
-    let shader = r#"
-        uniform mat4 u_ModelViewProj;
-        attribute vec2 a_Pos;
-        attribute vec2 a_Uv;
-        varying vec2 v_Uv;
-
-        void main() {
-            v_Uv = a_Uv;
-            gl_Position = u_ModelViewProj * vec4(a_Pos, 0.0, 1.0);
-        }
-    "#;
-    shader_version_string(api) + shader
-}
-
-fn fragment_shader(api: Api) -> String {
-    let mut header = shader_version_string(api);
-    if api == Api::OpenGlEs || api == Api::WebGl {
-        header += "precision mediump float;\n";
-    }
-    let shader = r#"
-        uniform vec4 u_Basic_color;
-        uniform sampler2D t_Tex;
-        varying vec2 v_Uv;
-
-        void main() {
-            gl_FragColor = u_Basic_color * texture2D(t_Tex, v_Uv);
-        }
-    "#;
-    header + shader
-}
-
-fn new_shader(
-    window: &glutin::GlWindow,
-    factory: &mut gfx_device_gl::Factory,
-) -> Program<gfx_device_gl::Resources> {
-    let api = window.get_api();
-    let vs = vertex_shader(api);
-    let fs = fragment_shader(api);
-    factory.link_program(vs.as_bytes(), fs.as_bytes()).unwrap()
-}
-
-fn new_pso(
-    factory: &mut gfx_device_gl::Factory,
-    program: &Program<gfx_device_gl::Resources>,
-    primitive: gfx::Primitive,
-) -> gfx::PipelineState<gfx_device_gl::Resources, pipe::Meta> {
-    let rasterizer = gfx::state::Rasterizer::new_fill();
-    let pso = factory.create_pipeline_from_program(program, primitive, rasterizer, pipe::new());
-    pso.unwrap()
-}
+use backend::{Backend, DrawCall};
+use audio::Audio;
+use sprite_sheet::{self, SpriteSheetDescriptor};
 
 fn load_font_from_vec(data: Vec<u8>) -> rusttype::Font<'static> {
     let collection = rusttype::FontCollection::from_bytes(data);
@@ -96,16 +34,8 @@ fn load_font(settings: &Settings) -> rusttype::Font<'static> {
     }
 }
 
-fn get_win_size(window: &glutin::Window) -> Size<i32> {
-    let (w, h) = window.get_inner_size().unwrap();
-    Size {
-        w: w as i32,
-        h: h as i32,
-    }
-}
-
 fn window_to_screen(context: &Context, x: f32, y: f32) -> Point {
-    let dpi = context.window.hidpi_factor();
+    let dpi = context.backend.hidpi_factor();
     let w = context.win_size.w as f32;
     let h = context.win_size.h as f32;
     let aspect_ratio = w / h;
@@ -127,10 +57,15 @@ pub fn projection_matrix(win_size: Size<i32>) -> Matrix4<f32> {
     }.into()
 }
 
+/// Some touchpads report the scrolling in pixels
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
 #[derive(Clone, Debug)]
 pub struct MouseState {
     pub last_press_pos: Point,
     pub pos: Point,
+    pub is_pressed: bool,
+    pub is_dragging: bool,
 }
 
 impl Default for MouseState {
@@ -138,79 +73,49 @@ impl Default for MouseState {
         Self {
             last_press_pos: Point(Vector2::zero()),
             pos: Point(Vector2::zero()),
+            is_pressed: false,
+            is_dragging: false,
         }
     }
 }
 
-fn gl_version() -> glutin::GlRequest {
-    glutin::GlRequest::GlThenGles {
-        opengles_version: (2, 0),
-        opengl_version: (2, 1),
-    }
-}
-
-// TODO: use gfx-rs generics, not gfx_device_gl types
 pub struct Context {
-    events_loop: glutin::EventsLoop,
+    backend: Box<Backend>,
     win_size: Size<i32>,
     projection_matrix: Matrix4<f32>,
     mouse: MouseState,
     should_close: bool,
     commands_tx: Sender<screen::Command>,
-    window: glutin::GlWindow,
     clear_color: [f32; 4],
-    device: gfx_device_gl::Device,
-    encoder: gfx::Encoder<gfx_device_gl::Resources, gfx_device_gl::CommandBuffer>,
-    data: pipe::Data<gfx_device_gl::Resources>,
-    pso: gfx::PipelineState<gfx_device_gl::Resources, pipe::Meta>,
-    factory: gfx_device_gl::Factory,
+    color: [f32; 4],
     font: rusttype::Font<'static>,
     start_time: time::Instant,
     events: Vec<Event>,
     settings: Settings,
+    audio: Audio,
     texture_cache: HashMap<PathBuf, Texture>,
     text_texture_cache: HashMap<String, Texture>,
+    sprite_sheet_descriptor_cache: HashMap<PathBuf, SpriteSheetDescriptor>,
 }
 
 impl Context {
-    pub(crate) fn new(tx: Sender<screen::Command>, settings: Settings) -> Context {
-        let window_builder = glutin::WindowBuilder::new().with_title("Zemeroth".to_string());
-        let context_builder = glutin::ContextBuilder::new()
-            .with_gl(gl_version())
-            .with_pixel_format(24, 8);
-        let events_loop = glutin::EventsLoop::new();
-        let (window, device, mut factory, out, out_depth) =
-            gfx_window_glutin::init(window_builder, context_builder, &events_loop);
-        let encoder = factory.create_command_buffer().into();
-        let program = new_shader(&window, &mut factory);
-        let primitive = gfx::Primitive::TriangleList;
-        let pso = new_pso(&mut factory, &program, primitive);
-        let sampler = factory.create_sampler_linear();
-        let win_size = get_win_size(&window);
+    pub(crate) fn new(
+        tx: Sender<screen::Command>,
+        settings: Settings,
+        backend: Box<Backend>,
+        audio: Audio,
+    ) -> Context {
+        let win_size = backend.win_size();
         let projection_matrix = projection_matrix(win_size);
-        let fake_texture = texture::load_raw(&mut factory, Size { w: 2, h: 2 }, &[0; 4]);
-        let fake_mesh = [];
-        let data = pipe::Data {
-            basic_color: [1.0, 1.0, 1.0, 1.0],
-            vbuf: factory.create_vertex_buffer(&fake_mesh),
-            texture: (fake_texture.raw, sampler),
-            out,
-            out_depth,
-            mvp: Matrix4::identity().into(),
-        };
         let font = load_font(&settings);
         Context {
             settings,
-            events_loop,
-            data,
+            backend,
+            audio,
             win_size,
             projection_matrix,
             clear_color: [1.0, 1.0, 1.0, 1.0],
-            window,
-            device,
-            factory,
-            encoder,
-            pso,
+            color: [1.0, 1.0, 1.0, 1.0],
             should_close: false,
             commands_tx: tx,
             font,
@@ -219,6 +124,7 @@ impl Context {
             events: Vec::new(),
             texture_cache: HashMap::new(),
             text_texture_cache: HashMap::new(),
+            sprite_sheet_descriptor_cache: HashMap::new(),
         }
     }
 
@@ -226,6 +132,14 @@ impl Context {
         &self.settings
     }
 
+    pub fn audio(&self) -> &Audio {
+        &self.audio
+    }
+
+    pub fn audio_mut(&mut self) -> &mut Audio {
+        &mut self.audio
+    }
+
     /// Loads a texture with caching
     pub(crate) fn load_texture<P: AsRef<Path>>(&mut self, path: P) -> Texture {
         let path = path.as_ref().to_path_buf();
@@ -237,6 +151,17 @@ impl Context {
         texture
     }
 
+    /// Loads a texture's sprite sheet descriptor with caching
+    pub(crate) fn load_sprite_sheet_descriptor(&mut self, path: &Path) -> SpriteSheetDescriptor {
+        if let Some(descriptor) = self.sprite_sheet_descriptor_cache.get(path) {
+            return descriptor.clone();
+        }
+        let descriptor = sprite_sheet::load_descriptor(path);
+        self.sprite_sheet_descriptor_cache
+            .insert(path.to_path_buf(), descriptor.clone());
+        descriptor
+    }
+
     pub(crate) fn text_texture(&mut self, label: &str) -> Texture {
         if let Some(texture) = self.text_texture_cache.get(label) {
             return texture.clone();
@@ -244,15 +169,14 @@ impl Context {
         let text_texture_height = self.settings().text_texture_height;
         let (texture_size, texture_data) =
             text::text_to_texture(self.font(), text_texture_height, label);
-        let texture = texture::load_raw(self.factory_mut(), texture_size, &texture_data);
+        let texture = texture::load_raw(self, texture_size, &texture_data);
         self.text_texture_cache
             .insert(label.to_owned(), texture.clone());
         texture
     }
 
     pub(crate) fn clear(&mut self) {
-        self.encoder.clear(&self.data.out, self.clear_color);
-        self.encoder.clear_depth(&self.data.out_depth, 1.0);
+        self.backend.clear(self.clear_color);
     }
 
     pub fn now(&self) -> Time {
@@ -264,22 +188,27 @@ impl Context {
     }
 
     pub(crate) fn flush(&mut self) {
-        self.encoder.flush(&mut self.device);
-        self.window.swap_buffers().expect("Can`t swap buffers");
-        self.device.cleanup();
+        self.backend.flush();
     }
 
     pub(crate) fn font(&self) -> &rusttype::Font {
         &self.font
     }
 
-    pub(crate) fn factory_mut(&mut self) -> &mut gfx_device_gl::Factory {
-        &mut self.factory
+    pub(crate) fn backend_mut(&mut self) -> &mut Backend {
+        &mut *self.backend
+    }
+
+    /// Draw calls of the current frame.
+    ///
+    /// Only the headless backend records them (see `Visualizer::new_headless`).
+    pub fn draw_calls(&self) -> &[DrawCall] {
+        self.backend.draw_calls()
     }
 
     // TODO: add `set_bg_color`
     pub fn set_color(&mut self, color: [f32; 4]) {
-        self.data.basic_color = color;
+        self.color = color;
     }
 
     fn mouse(&self) -> &MouseState {
@@ -287,10 +216,7 @@ impl Context {
     }
 
     pub(crate) fn draw_mesh(&mut self, mvp: Matrix4<f32>, mesh: &Mesh) {
-        self.data.mvp = mvp.into();
-        self.data.texture.0 = mesh.texture().raw.clone();
-        self.data.vbuf = mesh.vertex_buffer().clone();
-        self.encoder.draw(mesh.slice(), &self.pso, &self.data);
+        self.backend.draw_mesh(mvp, self.color, mesh);
     }
 
     pub fn aspect_ratio(&self) -> f32 {
@@ -302,16 +228,17 @@ impl Context {
     }
 
     pub fn pull_events(&mut self) -> Vec<Event> {
-        let mut raw_events = Vec::new();
-        self.events_loop.poll_events(|e| raw_events.push(e));
-        for event in &raw_events {
-            if let glutin::Event::WindowEvent { ref event, .. } = *event {
-                self.handle_event(event);
-            }
+        for event in self.backend.poll_events() {
+            self.handle_event(&event);
         }
         self.events.split_off(0)
     }
 
+    /// Queues an event as if it came from the window.
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
     fn handle_event(&mut self, event: &glutin::WindowEvent) {
         match *event {
             glutin::WindowEvent::Closed => {
@@ -321,48 +248,66 @@ impl Context {
                 state: Released,
                 button: MouseButton::Left,
                 ..
-            } => if self.is_tap() {
-                self.events.push(Event::Click {
-                    pos: self.mouse.pos,
-                });
-            },
+            } => {
+                self.release(true);
+            }
             glutin::WindowEvent::MouseInput {
                 state: Pressed,
                 button: MouseButton::Left,
                 ..
             } => {
-                self.mouse.last_press_pos = self.mouse.pos;
+                self.press();
             }
             glutin::WindowEvent::CursorMoved {
                 position: (x, y), ..
             } => {
-                self.mouse.pos = window_to_screen(self, x as f32, y as f32);
+                let pos = window_to_screen(self, x as f32, y as f32);
+                self.move_pointer(pos);
+            }
+            glutin::WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    glutin::MouseScrollDelta::LineDelta(_, y) => y,
+                    glutin::MouseScrollDelta::PixelDelta(_, y) => y / PIXELS_PER_SCROLL_LINE,
+                };
+                self.events.push(Event::Scroll { delta });
             }
             glutin::WindowEvent::Touch(touch_event) => {
                 let (x, y) = touch_event.location;
                 let pos = window_to_screen(self, x as f32, y as f32);
                 match touch_event.phase {
                     glutin::TouchPhase::Moved => {
-                        self.mouse.pos = pos;
+                        self.move_pointer(pos);
                     }
                     glutin::TouchPhase::Started => {
                         self.mouse.pos = pos;
-                        self.mouse.last_press_pos = pos;
+                        self.press();
                     }
                     glutin::TouchPhase::Ended => {
-                        self.mouse.pos = pos;
-                        if self.is_tap() {
-                            // TODO: check that this is an actual position!
-                            self.events.push(Event::Click {
-                                pos: self.mouse.pos,
-                            });
-                        }
+                        self.move_pointer(pos);
+                        self.release(true);
                     }
                     glutin::TouchPhase::Cancelled => {
-                        unimplemented!();
+                        self.release(false);
                     }
                 }
             }
+            glutin::WindowEvent::KeyboardInput {
+                input:
+                    glutin::KeyboardInput {
+                        state,
+                        virtual_keycode: Some(code),
+                        ..
+                    },
+                ..
+            } => if let Some(key) = Key::from_glutin(code) {
+                self.events.push(match state {
+                    Pressed => Event::KeyDown { key },
+                    Released => Event::KeyUp { key },
+                });
+            },
+            glutin::WindowEvent::ReceivedCharacter(ch) => if !ch.is_control() {
+                self.events.push(Event::Text { ch });
+            },
             glutin::WindowEvent::Resized(w, h) => {
                 if w == 0 || h == 0 {
                     return;
@@ -372,11 +317,7 @@ impl Context {
                     h: h as i32,
                 };
                 self.projection_matrix = projection_matrix(self.win_size);
-                gfx_window_glutin::update_views(
-                    &self.window,
-                    &mut self.data.out,
-                    &mut self.data.out_depth,
-                );
+                self.backend.resize(self.win_size);
                 let aspect_ratio = self.aspect_ratio();
                 self.events.push(Event::Resize { aspect_ratio });
             }
@@ -384,6 +325,45 @@ impl Context {
         }
     }
 
+    fn press(&mut self) {
+        self.mouse.last_press_pos = self.mouse.pos;
+        self.mouse.is_pressed = true;
+    }
+
+    fn move_pointer(&mut self, pos: Point) {
+        let delta = Point(pos.0 - self.mouse.pos.0);
+        self.mouse.pos = pos;
+        self.events.push(Event::PointerMove { pos });
+        if !self.mouse.is_pressed {
+            return;
+        }
+        if !self.mouse.is_dragging && !self.is_tap() {
+            self.mouse.is_dragging = true;
+            let pos = self.mouse.last_press_pos;
+            self.events.push(Event::DragStart { pos });
+            // The first drag event covers the whole way from the press position
+            let delta = Point(self.mouse.pos.0 - pos.0);
+            self.events.push(Event::Drag {
+                pos: self.mouse.pos,
+                delta,
+            });
+        } else if self.mouse.is_dragging {
+            self.events.push(Event::Drag { pos, delta });
+        }
+    }
+
+    /// `is_click_allowed` is `false` for cancelled touches.
+    fn release(&mut self, is_click_allowed: bool) {
+        let pos = self.mouse.pos;
+        if self.mouse.is_dragging {
+            self.events.push(Event::DragEnd { pos });
+        } else if is_click_allowed && self.mouse.is_pressed && self.is_tap() {
+            self.events.push(Event::Click { pos });
+        }
+        self.mouse.is_pressed = false;
+        self.mouse.is_dragging = false;
+    }
+
     /// Check if this was a tap or a swipe
     fn is_tap(&self) -> bool {
         let mouse = self.mouse();
@@ -1,7 +1,117 @@
+use glutin::VirtualKeyCode;
 use geom::Point;
 
 #[derive(Clone, Debug)]
 pub enum Event {
     Click { pos: Point },
     Resize { aspect_ratio: f32 },
+
+    /// The pointer has moved (pressed or not)
+    PointerMove { pos: Point },
+
+    /// The pointer has moved too far from the press position
+    /// for a click (see `Settings::tap_tolerance`). `pos` is the press position.
+    DragStart { pos: Point },
+
+    /// `delta` is the movement since the previous drag event.
+    Drag { pos: Point, delta: Point },
+
+    DragEnd { pos: Point },
+
+    /// Mouse wheel movement in lines, positive is up
+    Scroll { delta: f32 },
+
+    KeyDown { key: Key },
+    KeyUp { key: Key },
+
+    /// A typed character
+    Text { ch: char },
+}
+
+macro_rules! keys {
+    ($($key:ident => $code:ident,)*) => {
+        /// Keyboard keys. Named the same way in `Settings::key_bindings`.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            pub(crate) fn from_glutin(code: VirtualKeyCode) -> Option<Key> {
+                match code {
+                    $(VirtualKeyCode::$code => Some(Key::$key),)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+keys! {
+    A => A,
+    B => B,
+    C => C,
+    D => D,
+    E => E,
+    F => F,
+    G => G,
+    H => H,
+    I => I,
+    J => J,
+    K => K,
+    L => L,
+    M => M,
+    N => N,
+    O => O,
+    P => P,
+    Q => Q,
+    R => R,
+    S => S,
+    T => T,
+    U => U,
+    V => V,
+    W => W,
+    X => X,
+    Y => Y,
+    Z => Z,
+    Key0 => Key0,
+    Key1 => Key1,
+    Key2 => Key2,
+    Key3 => Key3,
+    Key4 => Key4,
+    Key5 => Key5,
+    Key6 => Key6,
+    Key7 => Key7,
+    Key8 => Key8,
+    Key9 => Key9,
+    F1 => F1,
+    F2 => F2,
+    F3 => F3,
+    F4 => F4,
+    F5 => F5,
+    F6 => F6,
+    F7 => F7,
+    F8 => F8,
+    F9 => F9,
+    F10 => F10,
+    F11 => F11,
+    F12 => F12,
+    Escape => Escape,
+    Enter => Return,
+    Space => Space,
+    Tab => Tab,
+    Backspace => Back,
+    Delete => Delete,
+    Left => Left,
+    Right => Right,
+    Up => Up,
+    Down => Down,
+    Home => Home,
+    End => End,
+    PageUp => PageUp,
+    PageDown => PageDown,
+    Minus => Minus,
+    Equals => Equals,
+    Comma => Comma,
+    Period => Period,
 }
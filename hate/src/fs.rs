@@ -1,3 +1,4 @@
+use std::io;
 use std::path::Path;
 
 #[cfg(not(target_os = "android"))]
@@ -35,6 +36,32 @@ pub fn load<P: AsRef<Path>>(path: P) -> Vec<u8> {
     }
 }
 
+/// Same as `load`, but returns `None` if the file can't be read
+#[cfg(not(target_os = "android"))]
+pub fn try_load<P: AsRef<Path>>(path: P) -> Option<Vec<u8>> {
+    use std::fs::File;
+    use std::io::Read;
+
+    check_assets_dir();
+    let mut buf = Vec::new();
+    let fullpath = &Path::new("assets").join(&path);
+    let mut file = File::open(&fullpath).ok()?;
+    file.read_to_end(&mut buf).ok()?;
+    Some(buf)
+}
+
+/// Writes the data to a file inside the 'assets' dir
+#[cfg(not(target_os = "android"))]
+pub fn save<P: AsRef<Path>>(path: P, data: &[u8]) -> io::Result<()> {
+    use std::fs::File;
+    use std::io::Write;
+
+    check_assets_dir();
+    let fullpath = &Path::new("assets").join(&path);
+    let mut file = File::create(&fullpath)?;
+    file.write_all(data)
+}
+
 #[cfg(target_os = "android")]
 pub fn load<P: AsRef<Path>>(path: P) -> Vec<u8> {
     use android_glue;
@@ -46,3 +73,18 @@ pub fn load<P: AsRef<Path>>(path: P) -> Vec<u8> {
         Err(_) => panic!("Can`t load asset '{}'", filename),
     }
 }
+
+#[cfg(target_os = "android")]
+pub fn try_load<P: AsRef<Path>>(path: P) -> Option<Vec<u8>> {
+    use android_glue;
+
+    let filename = path.as_ref().to_str().expect("Can`t convert Path to &str");
+    android_glue::load_asset(filename).ok()
+}
+
+#[cfg(target_os = "android")]
+pub fn save<P: AsRef<Path>>(_: P, _: &[u8]) -> io::Result<()> {
+    // Android assets are packed into the apk and are read-only
+    let message = "android assets are read-only";
+    Err(io::Error::new(io::ErrorKind::PermissionDenied, message))
+}
@@ -20,6 +20,28 @@ impl Size<f32> {
 #[derive(Copy, Clone, Debug)]
 pub struct Point(pub Vector2<f32>);
 
+/// A part of a texture in texture coordinates:
+/// `(0, 0)` is the top-left corner and `(1, 1)` is the bottom-right one.
+#[derive(Copy, Clone, Debug)]
+pub struct UvRect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl UvRect {
+    /// The whole texture
+    pub fn full() -> Self {
+        UvRect {
+            x: 0.0,
+            y: 0.0,
+            w: 1.0,
+            h: 1.0,
+        }
+    }
+}
+
 pub fn pos_to_matrix(pos: Point) -> Matrix4<f32> {
     Matrix4::from_translation(pos.0.extend(0.0))
 }
@@ -5,9 +5,15 @@
 #[cfg(target_os = "android")]
 extern crate android_glue;
 
+#[cfg(all(feature = "audio", not(target_os = "android")))]
+extern crate rodio;
+
 #[macro_use]
 extern crate gfx;
 
+#[macro_use]
+extern crate log;
+
 #[macro_use]
 extern crate serde_derive;
 
@@ -16,6 +22,7 @@ extern crate gfx_device_gl;
 extern crate gfx_window_glutin;
 extern crate glutin;
 extern crate png;
+extern crate ron;
 extern crate rusttype;
 
 pub mod gui;
@@ -33,14 +40,20 @@ mod visualizer;
 mod screen_stack;
 mod time;
 mod sprite;
+mod sprite_sheet;
 mod context;
 mod settings;
+mod backend;
+mod audio;
 
 pub use settings::Settings;
 pub use visualizer::Visualizer;
 pub use sprite::Sprite;
+pub use sprite_sheet::{Animation, AnimationMode, SpriteSheet, SpriteSheetDescriptor};
 pub use screen::Screen;
 pub use context::Context;
 pub use time::Time;
-pub use event::Event;
+pub use event::{Event, Key};
 pub use scene::Scene;
+pub use backend::DrawCall;
+pub use audio::{Audio, Sound};
@@ -1,17 +1,28 @@
 use gfx;
-use gfx::traits::FactoryExt;
 use gfx_device_gl;
 use context::Context;
 use texture::Texture;
-use geom::Size;
+use geom::{Point, Size, UvRect};
 use pipeline::Vertex;
 
 pub type VertexIndex = u16;
 
+/// A backend-specific mesh data
+#[derive(Clone, Debug)]
+pub enum RawMesh {
+    Gl {
+        slice: gfx::Slice<gfx_device_gl::Resources>,
+        vertex_buffer: gfx::handle::Buffer<gfx_device_gl::Resources, Vertex>,
+    },
+    Headless {
+        /// Triangles' vertices
+        vertices: Vec<Point>,
+    },
+}
+
 #[derive(Clone, Debug)]
 pub struct Mesh {
-    slice: gfx::Slice<gfx_device_gl::Resources>,
-    vertex_buffer: gfx::handle::Buffer<gfx_device_gl::Resources, Vertex>,
+    raw: RawMesh,
     texture: Texture,
 }
 
@@ -24,24 +35,33 @@ pub struct RMesh {
 
 impl RMesh {
     pub fn new(context: &mut Context, texture: Texture, size: Size<f32>) -> Self {
+        Self::with_uv(context, texture, size, UvRect::full())
+    }
+
+    /// Shows only a part of the texture, e.g. a frame of a sprite sheet
+    pub fn with_uv(context: &mut Context, texture: Texture, size: Size<f32>, uv: UvRect) -> Self {
         let w = size.w / 2.0;
         let h = size.h / 2.0;
+        let u_left = uv.x;
+        let u_right = uv.x + uv.w;
+        let v_top = uv.y;
+        let v_bottom = uv.y + uv.h;
         let vertices = &[
             Vertex {
                 pos: [-w, -h],
-                uv: [0.0, 1.0],
+                uv: [u_left, v_bottom],
             },
             Vertex {
                 pos: [-w, h],
-                uv: [0.0, 0.0],
+                uv: [u_left, v_top],
             },
             Vertex {
                 pos: [w, -h],
-                uv: [1.0, 1.0],
+                uv: [u_right, v_bottom],
             },
             Vertex {
                 pos: [w, h],
-                uv: [1.0, 0.0],
+                uv: [u_right, v_top],
             },
         ];
         let indices = &[0, 1, 2, 1, 2, 3];
@@ -65,21 +85,12 @@ impl Mesh {
         indices: &[VertexIndex],
         texture: Texture,
     ) -> Mesh {
-        let factory = context.factory_mut();
-        let (vertex_buffer, slice) = factory.create_vertex_buffer_with_slice(vertices, indices);
-        Mesh {
-            slice,
-            vertex_buffer,
-            texture,
-        }
-    }
-
-    pub fn slice(&self) -> &gfx::Slice<gfx_device_gl::Resources> {
-        &self.slice
+        let raw = context.backend_mut().create_mesh(vertices, indices);
+        Mesh { raw, texture }
     }
 
-    pub fn vertex_buffer(&self) -> &gfx::handle::Buffer<gfx_device_gl::Resources, Vertex> {
-        &self.vertex_buffer
+    pub fn raw(&self) -> &RawMesh {
+        &self.raw
     }
 
     pub fn texture(&self) -> &Texture {
@@ -0,0 +1,98 @@
+use std::f32;
+use time::Time;
+use sprite::Sprite;
+use sprite_sheet::{AnimationMode, SpriteSheet};
+use mesh::RMesh;
+use scene::Action;
+
+/// Plays a named animation of the sprite sheet on the sprite.
+///
+/// A newer `Animate` on the same sprite replaces this one: it finishes
+/// without touching the frames anymore.
+#[derive(Debug)]
+pub struct Animate {
+    sprite: Sprite,
+    frames: Vec<RMesh>,
+    fps: f32,
+    mode: AnimationMode,
+    time: Time,
+    animation_id: u32,
+}
+
+impl Animate {
+    /// Panics if there's no such animation (see `SpriteSheet::has_animation`).
+    pub fn new(sprite: &Sprite, sheet: &SpriteSheet, name: &str) -> Self {
+        let animation = match sheet.animation(name) {
+            Some(animation) => animation,
+            None => panic!("No animation '{}'", name),
+        };
+        let frames = animation
+            .frames
+            .iter()
+            .map(|&index| sheet.frame(index).clone())
+            .collect();
+        Self {
+            sprite: sprite.clone(),
+            frames,
+            fps: animation.fps,
+            mode: animation.mode,
+            time: Time(0.0),
+            animation_id: sprite.animation_id(),
+        }
+    }
+
+    /// Overrides the animation's mode from the sprite sheet.
+    pub fn with_mode(mut self, mode: AnimationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn is_replaced(&self) -> bool {
+        self.sprite.animation_id() != self.animation_id
+    }
+
+    fn frame_index(&self) -> usize {
+        let index = (self.time.0 * self.fps) as usize;
+        match self.mode {
+            AnimationMode::Loop => index % self.frames.len(),
+            AnimationMode::Once => index.min(self.frames.len() - 1),
+        }
+    }
+}
+
+impl Action for Animate {
+    /// Endless for the looped animations
+    fn duration(&self) -> Time {
+        match self.mode {
+            AnimationMode::Loop => Time(f32::INFINITY),
+            AnimationMode::Once => Time(self.frames.len() as f32 / self.fps),
+        }
+    }
+
+    fn begin(&mut self) {
+        self.animation_id = self.sprite.start_animation();
+        self.sprite.set_mesh(self.frames[0].clone());
+    }
+
+    fn update(&mut self, dtime: Time) {
+        if self.is_replaced() {
+            return;
+        }
+        let old_index = self.frame_index();
+        self.time.0 += dtime.0;
+        let index = self.frame_index();
+        if index != old_index {
+            self.sprite.set_mesh(self.frames[index].clone());
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        if self.is_replaced() {
+            return true;
+        }
+        match self.mode {
+            AnimationMode::Loop => false,
+            AnimationMode::Once => self.time.0 >= self.duration().0,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::fmt;
+use scene::Action;
+
+/// Calls a closure, for example to notify a screen that an animation is over.
+pub struct Callback {
+    f: Box<FnMut()>,
+}
+
+impl Callback {
+    pub fn new(f: Box<FnMut()>) -> Self {
+        Self { f }
+    }
+}
+
+impl fmt::Debug for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Callback")
+    }
+}
+
+impl Action for Callback {
+    fn begin(&mut self) {
+        (self.f)();
+    }
+}
@@ -1,14 +1,14 @@
 use time::Time;
 use sprite::Sprite;
 use scene::Action;
+use scene::action::{Easing, Lerp, Tween};
 
 #[derive(Debug)]
 pub struct ChangeColorTo {
     sprite: Sprite,
     from: [f32; 4],
     to: [f32; 4],
-    duration: Time,
-    progress: Time,
+    tween: Tween,
 }
 
 impl ChangeColorTo {
@@ -17,15 +17,19 @@ impl ChangeColorTo {
             sprite: sprite.clone(),
             from: sprite.color(),
             to,
-            duration,
-            progress: Time(0.0),
+            tween: Tween::new(duration),
         }
     }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.tween.set_easing(easing);
+        self
+    }
 }
 
 impl Action for ChangeColorTo {
     fn duration(&self) -> Time {
-        self.duration
+        self.tween.duration()
     }
 
     fn begin(&mut self) {
@@ -36,22 +40,13 @@ impl Action for ChangeColorTo {
         self.sprite.set_color(self.to);
     }
 
-    fn update(&mut self, mut dtime: Time) {
-        if dtime.0 + self.progress.0 > self.duration.0 {
-            dtime = Time(self.duration.0 - self.progress.0);
-        }
-        let k = self.progress.0 / self.duration.0;
-        let mut color = [0.0; 4];
-        for (i, color_i) in color.iter_mut().enumerate().take(4) {
-            let diff = self.to[i] - self.from[i];
-            *color_i = self.from[i] + diff * k;
-        }
-        self.sprite.set_color(color);
-        self.progress.0 += dtime.0;
+    fn update(&mut self, dtime: Time) {
+        let k = self.tween.update(dtime);
+        let value = Lerp::lerp(self.from, self.to, k);
+        self.sprite.set_color(value);
     }
 
     fn is_finished(&self) -> bool {
-        let eps = 0.00001;
-        self.progress.0 > (self.duration.0 - eps)
+        self.tween.is_finished()
     }
 }
@@ -0,0 +1,53 @@
+use time::Time;
+use sprite::Sprite;
+use geom::Size;
+use scene::Action;
+use scene::action::{Easing, Lerp, Tween};
+
+#[derive(Debug)]
+pub struct ChangeSizeTo {
+    sprite: Sprite,
+    from: Size<f32>,
+    to: Size<f32>,
+    tween: Tween,
+}
+
+impl ChangeSizeTo {
+    pub fn new(sprite: &Sprite, to: Size<f32>, duration: Time) -> Self {
+        Self {
+            sprite: sprite.clone(),
+            from: sprite.size(),
+            to,
+            tween: Tween::new(duration),
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.tween.set_easing(easing);
+        self
+    }
+}
+
+impl Action for ChangeSizeTo {
+    fn duration(&self) -> Time {
+        self.tween.duration()
+    }
+
+    fn begin(&mut self) {
+        self.from = self.sprite.size();
+    }
+
+    fn end(&mut self) {
+        self.sprite.set_size(self.to);
+    }
+
+    fn update(&mut self, dtime: Time) {
+        let k = self.tween.update(dtime);
+        let value = Lerp::lerp(self.from, self.to, k);
+        self.sprite.set_size(value);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.tween.is_finished()
+    }
+}
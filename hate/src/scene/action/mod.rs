@@ -9,10 +9,21 @@ pub use scene::action::fork::Fork;
 pub use scene::action::sleep::Sleep;
 pub use scene::action::change_color_to::ChangeColorTo;
 pub use scene::action::set_color::SetColor;
-
-// TODO: mod change_size
-// TODO: mod change_rotation
-// TODO: mod easing
+pub use scene::action::move_camera_to::MoveCameraTo;
+pub use scene::action::zoom_to::ZoomTo;
+pub use scene::action::change_size_to::ChangeSizeTo;
+pub use scene::action::rotate_by::RotateBy;
+pub use scene::action::rotate_to::RotateTo;
+pub use scene::action::parallel::Parallel;
+pub use scene::action::race::Race;
+pub use scene::action::repeat::{Loop, MakeAction, Repeat};
+pub use scene::action::callback::Callback;
+pub use scene::action::play_sound::PlaySound;
+pub use scene::action::play_music::PlayMusic;
+pub use scene::action::animate::Animate;
+pub use scene::action::stop_animation::StopAnimation;
+pub use scene::action::easing::Easing;
+pub use scene::action::tween::{Lerp, Tween};
 
 mod sequence;
 mod fork;
@@ -22,6 +33,21 @@ mod show;
 mod move_by;
 mod set_color;
 mod change_color_to;
+mod move_camera_to;
+mod zoom_to;
+mod change_size_to;
+mod rotate_by;
+mod rotate_to;
+mod parallel;
+mod race;
+mod repeat;
+mod callback;
+mod play_sound;
+mod play_music;
+mod animate;
+mod stop_animation;
+mod easing;
+mod tween;
 
 pub trait Action: Debug {
     fn begin(&mut self) {}
@@ -1,14 +1,14 @@
 use time::Time;
 use sprite::Sprite;
 use scene::Action;
+use scene::action::{Easing, Tween};
 use geom::Point;
 
 #[derive(Debug)]
 pub struct MoveBy {
     sprite: Sprite,
-    duration: Time,
     delta: Point,
-    progress: Time,
+    tween: Tween,
 }
 
 impl MoveBy {
@@ -16,29 +16,30 @@ impl MoveBy {
         Self {
             sprite: sprite.clone(),
             delta,
-            duration,
-            progress: Time(0.0),
+            tween: Tween::new(duration),
         }
     }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.tween.set_easing(easing);
+        self
+    }
 }
 
 impl Action for MoveBy {
     fn duration(&self) -> Time {
-        self.duration
+        self.tween.duration()
     }
 
-    fn update(&mut self, mut dtime: Time) {
-        let old_pos = self.sprite.pos();
-        if dtime.0 + self.progress.0 > self.duration.0 {
-            dtime = Time(self.duration.0 - self.progress.0);
-        }
-        let new_pos = Point(old_pos.0 + dtime.0 * self.delta.0 / self.duration.0);
+    fn update(&mut self, dtime: Time) {
+        // Relative movement: other actions may move the sprite at the same time
+        let old_k = self.tween.k();
+        let k = self.tween.update(dtime);
+        let new_pos = Point(self.sprite.pos().0 + self.delta.0 * (k - old_k));
         self.sprite.set_pos(new_pos);
-        self.progress.0 += dtime.0;
     }
 
     fn is_finished(&self) -> bool {
-        let eps = 0.00001;
-        self.progress.0 > (self.duration.0 - eps)
+        self.tween.is_finished()
     }
 }
@@ -0,0 +1,52 @@
+use time::Time;
+use geom::Point;
+use scene::{Action, Camera};
+use scene::action::{Easing, Lerp, Tween};
+
+#[derive(Debug)]
+pub struct MoveCameraTo {
+    camera: Camera,
+    from: Point,
+    to: Point,
+    tween: Tween,
+}
+
+impl MoveCameraTo {
+    pub fn new(camera: &Camera, to: Point, duration: Time) -> Self {
+        Self {
+            camera: camera.clone(),
+            from: camera.pos(),
+            to,
+            tween: Tween::new(duration),
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.tween.set_easing(easing);
+        self
+    }
+}
+
+impl Action for MoveCameraTo {
+    fn duration(&self) -> Time {
+        self.tween.duration()
+    }
+
+    fn begin(&mut self) {
+        self.from = self.camera.pos();
+    }
+
+    fn end(&mut self) {
+        self.camera.set_pos(self.to);
+    }
+
+    fn update(&mut self, dtime: Time) {
+        let k = self.tween.update(dtime);
+        let value = Lerp::lerp(self.from, self.to, k);
+        self.camera.set_pos(value);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.tween.is_finished()
+    }
+}
@@ -0,0 +1,68 @@
+use time::Time;
+use scene::Action;
+
+/// Runs all actions at the same time and finishes when all of them are finished.
+#[derive(Debug)]
+pub struct Parallel {
+    actions: Vec<Box<Action>>,
+    duration: Time,
+}
+
+impl Parallel {
+    pub fn new(actions: Vec<Box<Action>>) -> Self {
+        let mut duration = Time(0.0);
+        for action in &actions {
+            duration.0 = duration.0.max(action.duration().0);
+        }
+        Self { actions, duration }
+    }
+
+    fn end_finished_actions(&mut self) {
+        for action in &mut self.actions {
+            if action.is_finished() {
+                action.end();
+            }
+        }
+        self.actions.retain(|action| !action.is_finished());
+    }
+}
+
+impl Action for Parallel {
+    fn duration(&self) -> Time {
+        self.duration
+    }
+
+    fn begin(&mut self) {
+        for action in &mut self.actions {
+            action.begin();
+        }
+        // Skipping instant actions
+        self.end_finished_actions();
+    }
+
+    fn update(&mut self, dtime: Time) {
+        for action in &mut self.actions {
+            action.update(dtime);
+        }
+        self.end_finished_actions();
+    }
+
+    fn end(&mut self) {
+        assert!(self.actions.is_empty());
+    }
+
+    fn is_finished(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    fn try_fork(&mut self) -> Option<Box<Action>> {
+        let forked_action = self.actions
+            .iter_mut()
+            .filter_map(|action| action.try_fork())
+            .next();
+        if forked_action.is_some() {
+            self.end_finished_actions();
+        }
+        forked_action
+    }
+}
@@ -0,0 +1,24 @@
+use audio::{Audio, Sound};
+use scene::Action;
+
+/// Loops the music track, replacing the current one
+#[derive(Debug)]
+pub struct PlayMusic {
+    audio: Audio,
+    sound: Sound,
+}
+
+impl PlayMusic {
+    pub fn new(audio: &Audio, sound: &Sound) -> Self {
+        Self {
+            audio: audio.clone(),
+            sound: sound.clone(),
+        }
+    }
+}
+
+impl Action for PlayMusic {
+    fn begin(&mut self) {
+        self.audio.play_music(&self.sound);
+    }
+}
@@ -0,0 +1,23 @@
+use audio::{Audio, Sound};
+use scene::Action;
+
+#[derive(Debug)]
+pub struct PlaySound {
+    audio: Audio,
+    sound: Sound,
+}
+
+impl PlaySound {
+    pub fn new(audio: &Audio, sound: &Sound) -> Self {
+        Self {
+            audio: audio.clone(),
+            sound: sound.clone(),
+        }
+    }
+}
+
+impl Action for PlaySound {
+    fn begin(&mut self) {
+        self.audio.play(&self.sound);
+    }
+}
@@ -0,0 +1,71 @@
+use time::Time;
+use scene::Action;
+
+/// Runs all actions at the same time and finishes as soon as any of them is finished.
+///
+/// The other actions are stopped right where they are,
+/// without bringing them to their end states.
+#[derive(Debug)]
+pub struct Race {
+    actions: Vec<Box<Action>>,
+    duration: Time,
+}
+
+impl Race {
+    pub fn new(actions: Vec<Box<Action>>) -> Self {
+        let mut duration = Time(0.0);
+        for (i, action) in actions.iter().enumerate() {
+            if i == 0 || action.duration().0 < duration.0 {
+                duration = action.duration();
+            }
+        }
+        Self { actions, duration }
+    }
+
+    fn end_if_any_finished(&mut self) {
+        let winner = self.actions.iter().position(|action| action.is_finished());
+        if let Some(index) = winner {
+            self.actions.swap_remove(index).end();
+            self.actions.clear();
+        }
+    }
+}
+
+impl Action for Race {
+    fn duration(&self) -> Time {
+        self.duration
+    }
+
+    fn begin(&mut self) {
+        for action in &mut self.actions {
+            action.begin();
+        }
+        self.end_if_any_finished();
+    }
+
+    fn update(&mut self, dtime: Time) {
+        for action in &mut self.actions {
+            action.update(dtime);
+        }
+        self.end_if_any_finished();
+    }
+
+    fn end(&mut self) {
+        assert!(self.actions.is_empty());
+    }
+
+    fn is_finished(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    fn try_fork(&mut self) -> Option<Box<Action>> {
+        let forked_action = self.actions
+            .iter_mut()
+            .filter_map(|action| action.try_fork())
+            .next();
+        if forked_action.is_some() {
+            self.end_if_any_finished();
+        }
+        forked_action
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+use std::f32;
+use time::Time;
+use scene::Action;
+
+/// Makes a fresh instance of the repeated action for every run
+pub type MakeAction = Box<FnMut() -> Box<Action>>;
+
+/// Runs new instances of an action one after another, `times` times in total.
+pub struct Repeat {
+    make_action: MakeAction,
+    action: Option<Box<Action>>,
+
+    /// Runs left after the current one, `None` for endless loops
+    runs_left: Option<usize>,
+
+    duration: Time,
+}
+
+impl Repeat {
+    pub fn new(times: usize, mut make_action: MakeAction) -> Self {
+        let action = if times > 0 { Some(make_action()) } else { None };
+        let run_duration = action.as_ref().map_or(0.0, |action| action.duration().0);
+        Self {
+            make_action,
+            action,
+            runs_left: Some(times.saturating_sub(1)),
+            duration: Time(run_duration * times as f32),
+        }
+    }
+
+    fn new_endless(mut make_action: MakeAction) -> Self {
+        let action = make_action();
+        Self {
+            make_action,
+            action: Some(action),
+            runs_left: None,
+            duration: Time(f32::INFINITY),
+        }
+    }
+
+    /// Starts the next run if the current one is finished.
+    ///
+    /// At most one new run is started per call, so instant actions
+    /// in an endless loop can't hang the interpreter.
+    fn start_next_if_finished(&mut self) {
+        let is_finished = match self.action {
+            Some(ref action) => action.is_finished(),
+            None => return,
+        };
+        if !is_finished {
+            return;
+        }
+        self.action.take().unwrap().end();
+        match self.runs_left {
+            Some(0) => return,
+            Some(ref mut runs_left) => *runs_left -= 1,
+            None => {}
+        }
+        let mut action = (self.make_action)();
+        action.begin();
+        self.action = Some(action);
+    }
+}
+
+impl fmt::Debug for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Repeat")
+            .field("action", &self.action)
+            .field("runs_left", &self.runs_left)
+            .field("duration", &self.duration)
+            .finish()
+    }
+}
+
+impl Action for Repeat {
+    fn duration(&self) -> Time {
+        self.duration
+    }
+
+    fn begin(&mut self) {
+        if let Some(ref mut action) = self.action {
+            action.begin();
+        }
+    }
+
+    fn update(&mut self, dtime: Time) {
+        if let Some(ref mut action) = self.action {
+            action.update(dtime);
+        }
+        self.start_next_if_finished();
+    }
+
+    fn end(&mut self) {
+        assert!(self.action.is_none());
+    }
+
+    fn is_finished(&self) -> bool {
+        self.action.is_none()
+    }
+
+    fn try_fork(&mut self) -> Option<Box<Action>> {
+        let forked_action = match self.action {
+            Some(ref mut action) => action.try_fork(),
+            None => None,
+        };
+        if forked_action.is_some() {
+            self.start_next_if_finished();
+        }
+        forked_action
+    }
+}
+
+/// Runs new instances of an action one after another, forever.
+///
+/// Its `duration` is infinite, so cancel it with `Scene::cancel_action`
+/// instead of waiting for it to finish.
+#[derive(Debug)]
+pub struct Loop {
+    repeat: Repeat,
+}
+
+impl Loop {
+    pub fn new(make_action: MakeAction) -> Self {
+        Self {
+            repeat: Repeat::new_endless(make_action),
+        }
+    }
+}
+
+impl Action for Loop {
+    fn duration(&self) -> Time {
+        self.repeat.duration()
+    }
+
+    fn begin(&mut self) {
+        self.repeat.begin();
+    }
+
+    fn update(&mut self, dtime: Time) {
+        self.repeat.update(dtime);
+    }
+
+    fn end(&mut self) {
+        self.repeat.end();
+    }
+
+    fn is_finished(&self) -> bool {
+        self.repeat.is_finished()
+    }
+
+    fn try_fork(&mut self) -> Option<Box<Action>> {
+        self.repeat.try_fork()
+    }
+}
@@ -0,0 +1,44 @@
+use time::Time;
+use sprite::Sprite;
+use scene::Action;
+use scene::action::{Easing, Tween};
+
+/// `angle` is in radians, counterclockwise.
+#[derive(Debug)]
+pub struct RotateBy {
+    sprite: Sprite,
+    angle: f32,
+    tween: Tween,
+}
+
+impl RotateBy {
+    pub fn new(sprite: &Sprite, angle: f32, duration: Time) -> Self {
+        Self {
+            sprite: sprite.clone(),
+            angle,
+            tween: Tween::new(duration),
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.tween.set_easing(easing);
+        self
+    }
+}
+
+impl Action for RotateBy {
+    fn duration(&self) -> Time {
+        self.tween.duration()
+    }
+
+    fn update(&mut self, dtime: Time) {
+        let old_k = self.tween.k();
+        let k = self.tween.update(dtime);
+        let rotation = self.sprite.rotation() + self.angle * (k - old_k);
+        self.sprite.set_rotation(rotation);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.tween.is_finished()
+    }
+}
@@ -0,0 +1,54 @@
+use time::Time;
+use sprite::Sprite;
+use scene::Action;
+use scene::action::{Easing, Lerp, Tween};
+
+/// `to` is in radians, counterclockwise. Doesn't pick the shortest way:
+/// rotating from `0` to `2 * PI` is a full turn.
+#[derive(Debug)]
+pub struct RotateTo {
+    sprite: Sprite,
+    from: f32,
+    to: f32,
+    tween: Tween,
+}
+
+impl RotateTo {
+    pub fn new(sprite: &Sprite, to: f32, duration: Time) -> Self {
+        Self {
+            sprite: sprite.clone(),
+            from: sprite.rotation(),
+            to,
+            tween: Tween::new(duration),
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.tween.set_easing(easing);
+        self
+    }
+}
+
+impl Action for RotateTo {
+    fn duration(&self) -> Time {
+        self.tween.duration()
+    }
+
+    fn begin(&mut self) {
+        self.from = self.sprite.rotation();
+    }
+
+    fn end(&mut self) {
+        self.sprite.set_rotation(self.to);
+    }
+
+    fn update(&mut self, dtime: Time) {
+        let k = self.tween.update(dtime);
+        let value = Lerp::lerp(self.from, self.to, k);
+        self.sprite.set_rotation(value);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.tween.is_finished()
+    }
+}
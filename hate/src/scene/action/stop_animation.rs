@@ -0,0 +1,22 @@
+use sprite::Sprite;
+use scene::Action;
+
+/// Finishes the current `Animate` action of the sprite, leaving its current frame
+#[derive(Debug)]
+pub struct StopAnimation {
+    sprite: Sprite,
+}
+
+impl StopAnimation {
+    pub fn new(sprite: &Sprite) -> Self {
+        Self {
+            sprite: sprite.clone(),
+        }
+    }
+}
+
+impl Action for StopAnimation {
+    fn begin(&mut self) {
+        self.sprite.start_animation();
+    }
+}
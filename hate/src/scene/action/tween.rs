@@ -0,0 +1,96 @@
+use cgmath::Vector2;
+use time::Time;
+use geom::{Point, Size};
+use scene::action::Easing;
+
+/// Values that can be interpolated by `Tween`-based actions
+pub trait Lerp: Copy {
+    /// `k` is usually in the `0.0..1.0` range, but some easings overshoot it.
+    fn lerp(from: Self, to: Self, k: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(from: Self, to: Self, k: f32) -> Self {
+        from + (to - from) * k
+    }
+}
+
+impl Lerp for Point {
+    fn lerp(from: Self, to: Self, k: f32) -> Self {
+        Point(from.0 + (to.0 - from.0) * k)
+    }
+}
+
+impl Lerp for Size<f32> {
+    fn lerp(from: Self, to: Self, k: f32) -> Self {
+        Size {
+            w: Lerp::lerp(from.w, to.w, k),
+            h: Lerp::lerp(from.h, to.h, k),
+        }
+    }
+}
+
+impl Lerp for [f32; 4] {
+    fn lerp(from: Self, to: Self, k: f32) -> Self {
+        let mut value = [0.0; 4];
+        for (i, value_i) in value.iter_mut().enumerate() {
+            *value_i = Lerp::lerp(from[i], to[i], k);
+        }
+        value
+    }
+}
+
+impl Lerp for Vector2<f32> {
+    fn lerp(from: Self, to: Self, k: f32) -> Self {
+        from + (to - from) * k
+    }
+}
+
+/// Eased progress of a time-based action.
+///
+/// A new interpolating action only needs to store a `Tween`,
+/// call `update` and `Lerp` its value with the returned `k`.
+#[derive(Clone, Copy, Debug)]
+pub struct Tween {
+    duration: Time,
+    progress: Time,
+    easing: Easing,
+}
+
+impl Tween {
+    pub fn new(duration: Time) -> Self {
+        Self {
+            duration,
+            progress: Time(0.0),
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    pub fn duration(&self) -> Time {
+        self.duration
+    }
+
+    /// Eased progress: `0.0` at the beginning, `1.0` at the end
+    pub fn k(&self) -> f32 {
+        if self.duration.0 <= 0.0 {
+            return 1.0;
+        }
+        let t = (self.progress.0 / self.duration.0).min(1.0);
+        self.easing.apply(t)
+    }
+
+    /// Advances the time (never beyond the duration) and returns the new `k`.
+    pub fn update(&mut self, dtime: Time) -> f32 {
+        self.progress.0 = (self.progress.0 + dtime.0).min(self.duration.0);
+        self.k()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        let eps = 0.00001;
+        self.progress.0 > (self.duration.0 - eps)
+    }
+}
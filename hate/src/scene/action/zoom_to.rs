@@ -0,0 +1,51 @@
+use time::Time;
+use scene::{Action, Camera};
+use scene::action::{Easing, Lerp, Tween};
+
+#[derive(Debug)]
+pub struct ZoomTo {
+    camera: Camera,
+    from: f32,
+    to: f32,
+    tween: Tween,
+}
+
+impl ZoomTo {
+    pub fn new(camera: &Camera, to: f32, duration: Time) -> Self {
+        Self {
+            camera: camera.clone(),
+            from: camera.zoom(),
+            to,
+            tween: Tween::new(duration),
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.tween.set_easing(easing);
+        self
+    }
+}
+
+impl Action for ZoomTo {
+    fn duration(&self) -> Time {
+        self.tween.duration()
+    }
+
+    fn begin(&mut self) {
+        self.from = self.camera.zoom();
+    }
+
+    fn end(&mut self) {
+        self.camera.set_zoom(self.to);
+    }
+
+    fn update(&mut self, dtime: Time) {
+        let k = self.tween.update(dtime);
+        let value = Lerp::lerp(self.from, self.to, k);
+        self.camera.set_zoom(value);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.tween.is_finished()
+    }
+}
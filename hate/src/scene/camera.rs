@@ -0,0 +1,103 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use cgmath::{Matrix4, Vector2, Zero};
+use geom::{self, Point};
+
+/// The camera's position is kept inside of this rectangle.
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+#[derive(Debug)]
+struct CameraData {
+    pos: Point,
+    zoom: f32,
+    bounds: Option<Bounds>,
+}
+
+/// A shared handle: the scene and camera actions can hold clones of it.
+///
+/// `pos` is the world point shown at the center of the screen,
+/// `zoom` is how many times the world is scaled up.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    data: Rc<RefCell<CameraData>>,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        let data = CameraData {
+            pos: Point(Vector2::zero()),
+            zoom: 1.0,
+            bounds: None,
+        };
+        Self {
+            data: Rc::new(RefCell::new(data)),
+        }
+    }
+
+    pub fn pos(&self) -> Point {
+        self.data.borrow().pos
+    }
+
+    /// The position is clamped by the bounds.
+    pub fn set_pos(&mut self, pos: Point) {
+        let mut data = self.data.borrow_mut();
+        data.pos = match data.bounds {
+            Some(bounds) => clamp(pos, bounds),
+            None => pos,
+        };
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.data.borrow().zoom
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        assert!(zoom > 0.0);
+        self.data.borrow_mut().zoom = zoom;
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.data.borrow().bounds
+    }
+
+    pub fn set_bounds(&mut self, bounds: Option<Bounds>) {
+        self.data.borrow_mut().bounds = bounds;
+        let pos = self.pos();
+        self.set_pos(pos);
+    }
+
+    /// View matrix: world coordinates to screen coordinates
+    pub fn matrix(&self) -> Matrix4<f32> {
+        let data = self.data.borrow();
+        let pos = Point(-data.pos.0);
+        Matrix4::from_scale(data.zoom) * geom::pos_to_matrix(pos)
+    }
+
+    /// Converts a point from the screen coordinates (like `Event::Click`'s `pos`)
+    /// to the world coordinates.
+    pub fn screen_to_world(&self, point: Point) -> Point {
+        let data = self.data.borrow();
+        Point(point.0 / data.zoom + data.pos.0)
+    }
+
+    pub fn world_to_screen(&self, point: Point) -> Point {
+        let data = self.data.borrow();
+        Point((point.0 - data.pos.0) * data.zoom)
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn clamp(pos: Point, bounds: Bounds) -> Point {
+    let x = pos.0.x.max(bounds.min.0.x).min(bounds.max.0.x);
+    let y = pos.0.y.max(bounds.min.0.y).min(bounds.max.0.y);
+    Point(Vector2 { x, y })
+}
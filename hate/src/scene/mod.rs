@@ -5,9 +5,12 @@ use context::Context;
 use time::Time;
 
 pub use scene::action::Action;
+pub use scene::camera::{Bounds, Camera};
 
 pub mod action;
 
+mod camera;
+
 #[derive(Debug)]
 struct LayerData {
     sprites: Vec<Sprite>,
@@ -48,6 +51,8 @@ impl Default for Layer {
 pub struct Scene {
     layers: Vec<Layer>,
     interpreter: ActionInterpreter,
+    camera: Camera,
+    speed: f32,
 }
 
 impl Scene {
@@ -55,30 +60,98 @@ impl Scene {
         Self {
             layers,
             interpreter: ActionInterpreter::new(),
+            camera: Camera::new(),
+            speed: 1.0,
         }
     }
 
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// Animation speed factor: `2.0` plays all actions twice as fast.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        assert!(speed >= 0.0);
+        self.speed = speed;
+    }
+
     pub fn draw(&self, context: &mut Context) {
-        let projection_matrix = context.projection_matrix();
+        let matrix = context.projection_matrix() * self.camera.matrix();
         for layer in &self.layers {
             for sprite in &layer.data.borrow().sprites {
-                sprite.draw(context, projection_matrix);
+                sprite.draw(context, matrix);
             }
         }
     }
 
     pub fn add_action(&mut self, action: Box<Action>) {
-        self.interpreter.add(action);
+        self.interpreter.add(None, action);
+    }
+
+    /// Adds an action that can be cancelled later with `cancel_action`.
+    ///
+    /// Actions forked by it get the same name.
+    pub fn add_named_action(&mut self, name: &str, action: Box<Action>) {
+        self.interpreter.add(Some(name.into()), action);
+    }
+
+    pub fn is_action_running(&self, name: &str) -> bool {
+        self.interpreter.is_running(name)
+    }
+
+    /// Stops all actions with this name right where they are,
+    /// without bringing them to their end states.
+    ///
+    /// Returns `false` if there were no such actions.
+    pub fn cancel_action(&mut self, name: &str) -> bool {
+        self.interpreter.cancel(name)
+    }
+
+    /// Instantly runs all current actions (and everything they fork) to their end states.
+    ///
+    /// Endless actions (like looped idle animations) can't be finished:
+    /// they're left running untouched.
+    pub fn finish_all(&mut self) {
+        self.interpreter.finish_all();
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.interpreter.actions.is_empty()
     }
 
     pub fn tick(&mut self, dtime: Time) {
-        self.interpreter.tick(dtime);
+        self.interpreter.tick(Time(dtime.0 * self.speed));
     }
 }
 
+/// Big enough to finish any sane action in one update
+const FINISH_DTIME: Time = Time(1_000_000.0);
+
+/// Every iteration finishes at least one timed action of each sequence,
+/// so only a misbehaving action can hit this limit
+const MAX_FINISH_ITERATIONS: usize = 10_000;
+
+fn is_endless(action: &Action) -> bool {
+    action.duration().0.is_infinite()
+}
+
+#[derive(Debug)]
+struct RunningAction {
+    name: Option<String>,
+    action: Box<Action>,
+}
+
 #[derive(Debug)]
 struct ActionInterpreter {
-    actions: Vec<Box<Action>>,
+    actions: Vec<RunningAction>,
 }
 
 impl ActionInterpreter {
@@ -88,25 +161,256 @@ impl ActionInterpreter {
         }
     }
 
-    pub fn add(&mut self, mut action: Box<Action>) {
+    pub fn add(&mut self, name: Option<String>, mut action: Box<Action>) {
         action.begin();
-        self.actions.push(action);
+        self.actions.push(RunningAction { name, action });
+    }
+
+    pub fn is_running(&self, name: &str) -> bool {
+        self.actions
+            .iter()
+            .any(|running| running.name.as_ref().map(|s| s.as_str()) == Some(name))
+    }
+
+    pub fn cancel(&mut self, name: &str) -> bool {
+        let len_before = self.actions.len();
+        self.actions
+            .retain(|running| running.name.as_ref().map(|s| s.as_str()) != Some(name));
+        self.actions.len() != len_before
+    }
+
+    pub fn finish_all(&mut self) {
+        let mut endless = Vec::new();
+        for _ in 0..MAX_FINISH_ITERATIONS {
+            // Endless actions (including the forked ones) aren't ticked at all
+            let (new_endless, finite): (Vec<_>, Vec<_>) = self.actions
+                .drain(..)
+                .partition(|running| is_endless(&*running.action));
+            endless.extend(new_endless);
+            self.actions = finite;
+            if self.actions.is_empty() {
+                break;
+            }
+            self.tick(FINISH_DTIME);
+        }
+        // Actions that never finish despite their finite durations are dropped
+        self.actions = endless;
     }
 
     pub fn tick(&mut self, dtime: Time) {
         let mut forked_actions = Vec::new();
-        for action in &mut self.actions {
+        for running in &mut self.actions {
+            let action = &mut running.action;
             action.update(dtime);
             while let Some(forked_action) = action.try_fork() {
-                forked_actions.push(forked_action);
+                forked_actions.push((running.name.clone(), forked_action));
             }
             if action.is_finished() {
                 action.end();
             }
         }
-        for action in forked_actions {
-            self.add(action);
+        for (name, action) in forked_actions {
+            self.add(name, action);
+        }
+        self.actions.retain(|running| !running.action.is_finished());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use time::Time;
+    use scene::action::{self, Action};
+    use super::{ActionInterpreter, Scene};
+
+    fn sleep(seconds: f32) -> Box<Action> {
+        Box::new(action::Sleep::new(Time(seconds)))
+    }
+
+    /// Returns a counter and an action that increments it.
+    fn counter() -> (Rc<Cell<i32>>, Box<Action>) {
+        let count = Rc::new(Cell::new(0));
+        let action = counter_action(&count);
+        (count, action)
+    }
+
+    fn counter_action(count: &Rc<Cell<i32>>) -> Box<Action> {
+        let count = count.clone();
+        Box::new(action::Callback::new(Box::new(move || {
+            count.set(count.get() + 1);
+        })))
+    }
+
+    /// An endless loop that increments the counter every half a second
+    fn counter_loop(count: &Rc<Cell<i32>>) -> Box<Action> {
+        let count = count.clone();
+        Box::new(action::Loop::new(Box::new(move || -> Box<Action> {
+            Box::new(action::Sequence::new(vec![
+                sleep(0.5),
+                counter_action(&count),
+            ]))
+        })))
+    }
+
+    fn tick(scene: &mut Scene, times: usize) {
+        for _ in 0..times {
+            scene.tick(Time(0.6));
         }
-        self.actions.retain(|action| !action.is_finished());
+    }
+
+    #[test]
+    fn parallel() {
+        let parallel = action::Parallel::new(vec![sleep(0.5), sleep(1.0)]);
+        assert_eq!(parallel.duration(), Time(1.0));
+        let (count, callback) = counter();
+        let mut scene = Scene::new(Vec::new());
+        scene.add_action(Box::new(action::Sequence::new(vec![
+            Box::new(parallel),
+            callback,
+        ])));
+        tick(&mut scene, 1);
+        assert_eq!(count.get(), 0, "the longest action isn't finished yet");
+        tick(&mut scene, 1);
+        assert_eq!(count.get(), 1);
+        assert!(scene.is_idle());
+    }
+
+    #[test]
+    fn race() {
+        let race = action::Race::new(vec![sleep(1.0), sleep(0.5)]);
+        assert_eq!(race.duration(), Time(0.5));
+        let (count, callback) = counter();
+        let mut scene = Scene::new(Vec::new());
+        scene.add_action(Box::new(action::Sequence::new(vec![Box::new(race), callback])));
+        tick(&mut scene, 1);
+        assert_eq!(count.get(), 1, "the shortest action has finished");
+        assert!(scene.is_idle());
+    }
+
+    #[test]
+    fn repeat() {
+        let count = Rc::new(Cell::new(0));
+        let count_clone = count.clone();
+        let repeat = action::Repeat::new(
+            3,
+            Box::new(move || -> Box<Action> {
+                Box::new(action::Sequence::new(vec![
+                    sleep(0.5),
+                    counter_action(&count_clone),
+                ]))
+            }),
+        );
+        assert_eq!(repeat.duration(), Time(1.5));
+        let mut scene = Scene::new(Vec::new());
+        scene.add_action(Box::new(repeat));
+        tick(&mut scene, 2);
+        assert_eq!(count.get(), 2);
+        assert!(!scene.is_idle());
+        tick(&mut scene, 1);
+        assert_eq!(count.get(), 3);
+        assert!(scene.is_idle());
+    }
+
+    #[test]
+    fn loop_and_cancel() {
+        let count = Rc::new(Cell::new(0));
+        let action_loop = counter_loop(&count);
+        assert!(action_loop.duration().0.is_infinite());
+        let mut scene = Scene::new(Vec::new());
+        scene.add_named_action("loop", action_loop);
+        tick(&mut scene, 10);
+        assert_eq!(count.get(), 10);
+        assert!(scene.is_action_running("loop"));
+        assert!(scene.cancel_action("loop"));
+        assert!(!scene.is_action_running("loop"));
+        assert!(!scene.cancel_action("loop"));
+        assert!(scene.is_idle());
+    }
+
+    #[test]
+    fn speed() {
+        let (count, callback) = counter();
+        let mut scene = Scene::new(Vec::new());
+        scene.set_speed(2.0);
+        scene.add_action(Box::new(action::Sequence::new(vec![sleep(1.0), callback])));
+        tick(&mut scene, 1);
+        assert_eq!(count.get(), 1, "1.2 seconds have passed for the scene");
+    }
+
+    #[test]
+    fn finish_all_runs_forked_actions() {
+        let (count, callback) = counter();
+        let forked = Box::new(action::Sequence::new(vec![sleep(5.0), callback]));
+        let mut interpreter = ActionInterpreter::new();
+        interpreter.add(
+            None,
+            Box::new(action::Sequence::new(vec![
+                sleep(10.0),
+                Box::new(action::Fork::new(forked)),
+                sleep(10.0),
+            ])),
+        );
+        interpreter.finish_all();
+        assert_eq!(count.get(), 1, "forked actions are finished too");
+        assert!(interpreter.actions.is_empty());
+    }
+
+    #[test]
+    fn finish_all_leaves_endless_actions_running() {
+        let loop_count = Rc::new(Cell::new(0));
+        let (count, callback) = counter();
+        let mut interpreter = ActionInterpreter::new();
+        interpreter.add(Some("idle".into()), counter_loop(&loop_count));
+        interpreter.add(
+            None,
+            Box::new(action::Sequence::new(vec![sleep(3.0), callback])),
+        );
+        interpreter.finish_all();
+        assert_eq!(count.get(), 1);
+        assert_eq!(loop_count.get(), 0, "endless actions aren't ticked");
+        assert_eq!(interpreter.actions.len(), 1);
+        assert!(interpreter.is_running("idle"));
+        interpreter.tick(Time(0.6));
+        assert_eq!(loop_count.get(), 1);
+    }
+
+    #[test]
+    fn cancel_only_named_actions() {
+        let (count, callback) = counter();
+        let mut interpreter = ActionInterpreter::new();
+        interpreter.add(Some("a".into()), sleep(1.0));
+        interpreter.add(Some("b".into()), sleep(1.0));
+        interpreter.add(
+            None,
+            Box::new(action::Sequence::new(vec![sleep(1.0), callback])),
+        );
+        assert!(interpreter.cancel("a"));
+        assert!(!interpreter.is_running("a"));
+        assert!(interpreter.is_running("b"));
+        assert_eq!(interpreter.actions.len(), 2);
+        interpreter.tick(Time(1.5));
+        assert_eq!(count.get(), 1, "unnamed actions aren't cancelled");
+        assert!(interpreter.actions.is_empty());
+    }
+
+    #[test]
+    fn forked_actions_inherit_names() {
+        let (count, callback) = counter();
+        let forked = Box::new(action::Sequence::new(vec![sleep(1.0), callback]));
+        let mut interpreter = ActionInterpreter::new();
+        interpreter.add(
+            Some("attack".into()),
+            Box::new(action::Sequence::new(vec![
+                sleep(0.5),
+                Box::new(action::Fork::new(forked)),
+            ])),
+        );
+        interpreter.tick(Time(0.6));
+        assert_eq!(interpreter.actions.len(), 1, "only the forked action is left");
+        assert!(interpreter.is_running("attack"));
+        assert!(interpreter.cancel("attack"));
+        interpreter.tick(Time(2.0));
+        assert_eq!(count.get(), 0, "the forked action was cancelled too");
     }
 }
@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::default::Default;
 use std::path::PathBuf;
+use event::Key;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -8,6 +10,24 @@ pub struct Settings {
     pub text_texture_height: f32,
     pub font: PathBuf,
     pub max_fps: f32,
+
+    /// Volume of the sound effects, `0.0..1.0`
+    pub sound_volume: f32,
+
+    /// Volume of the music, `0.0..1.0`
+    pub music_volume: f32,
+
+    pub mute: bool,
+
+    /// Keys of the named actions, e.g. `{"end_turn": [Enter, E]}`
+    pub key_bindings: HashMap<String, Vec<Key>>,
+}
+
+impl Settings {
+    /// `None` if the action has no configured keys.
+    pub fn keys(&self, action: &str) -> Option<&[Key]> {
+        self.key_bindings.get(action).map(|keys| keys.as_slice())
+    }
 }
 
 impl Default for Settings {
@@ -17,6 +37,10 @@ impl Default for Settings {
             text_texture_height: 80.0,
             font: "<embedded>".into(),
             max_fps: 60.0,
+            sound_volume: 1.0,
+            music_volume: 0.5,
+            mute: false,
+            key_bindings: HashMap::new(),
         }
     }
 }
@@ -1,9 +1,10 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::path::Path;
-use cgmath::{Matrix4, Vector2, Zero};
+use cgmath::{Matrix4, Rad, Vector2, Zero};
 use context::Context;
 use mesh::RMesh;
+use sprite_sheet::SpriteSheet;
 use geom::{self, Point, Size};
 
 #[derive(Debug, Clone)]
@@ -16,6 +17,18 @@ struct SpriteData {
     mesh: RMesh,
     pos: Point,
     color: [f32; 4],
+    size: Size<f32>,
+
+    /// Counterclockwise, in radians
+    rotation: f32,
+
+    /// Relative to the mesh's size: `[0, 0]` is the center, `[-0.5, -0.5]` is
+    /// the bottom-left corner. The sprite is rotated and scaled around this point
+    /// and `pos` is the position of this point.
+    anchor: Point,
+
+    /// Incremented by every new `Animate` action, so the older ones know they're replaced
+    animation_id: u32,
 }
 
 impl Sprite {
@@ -31,14 +44,40 @@ impl Sprite {
     }
 
     pub fn from_mesh(mesh: RMesh) -> Self {
-        let pos = Point(Vector2::zero());
-        let color = [1.0, 1.0, 1.0, 1.0];
-        let data = SpriteData { mesh, pos, color };
+        let data = SpriteData {
+            pos: Point(Vector2::zero()),
+            color: [1.0, 1.0, 1.0, 1.0],
+            size: mesh.size(),
+            rotation: 0.0,
+            anchor: Point(Vector2::zero()),
+            animation_id: 0,
+            mesh,
+        };
         Self {
             data: Rc::new(RefCell::new(data)),
         }
     }
 
+    /// Shows the first frame of the sheet. Use `action::Animate` to change frames.
+    pub fn from_sprite_sheet(sheet: &SpriteSheet) -> Self {
+        Self::from_mesh(sheet.frame(0).clone())
+    }
+
+    pub(crate) fn set_mesh(&mut self, mesh: RMesh) {
+        self.data.borrow_mut().mesh = mesh;
+    }
+
+    pub(crate) fn animation_id(&self) -> u32 {
+        self.data.borrow().animation_id
+    }
+
+    /// Returns the id of the new current animation
+    pub(crate) fn start_animation(&mut self) -> u32 {
+        let mut data = self.data.borrow_mut();
+        data.animation_id = data.animation_id.wrapping_add(1);
+        data.animation_id
+    }
+
     pub fn is_same(&self, other: &Self) -> bool {
         Rc::ptr_eq(&self.data, &other.data)
     }
@@ -59,13 +98,44 @@ impl Sprite {
         self.data.borrow().color
     }
 
+    /// Current size: the mesh is scaled to it
     pub fn size(&self) -> Size<f32> {
-        self.data.borrow().mesh.size()
+        self.data.borrow().size
+    }
+
+    pub fn set_size(&mut self, size: Size<f32>) {
+        self.data.borrow_mut().size = size;
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.data.borrow().rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.data.borrow_mut().rotation = rotation;
+    }
+
+    pub fn anchor(&self) -> Point {
+        self.data.borrow().anchor
+    }
+
+    pub fn set_anchor(&mut self, anchor: Point) {
+        self.data.borrow_mut().anchor = anchor;
     }
 
     pub fn draw(&self, context: &mut Context, parent_matrix: Matrix4<f32>) {
         let data = self.data.borrow();
-        let model_matrix = geom::pos_to_matrix(data.pos);
+        let mesh_size = data.mesh.size();
+        let anchor_offset = Point(Vector2 {
+            x: -data.anchor.0.x * mesh_size.w,
+            y: -data.anchor.0.y * mesh_size.h,
+        });
+        let scale_x = data.size.w / mesh_size.w;
+        let scale_y = data.size.h / mesh_size.h;
+        let translation = geom::pos_to_matrix(data.pos);
+        let rotation = Matrix4::from_angle_z(Rad(data.rotation));
+        let scale = Matrix4::from_nonuniform_scale(scale_x, scale_y, 1.0);
+        let model_matrix = translation * rotation * scale * geom::pos_to_matrix(anchor_offset);
         context.set_color(data.color);
         context.draw_mesh(parent_matrix * model_matrix, data.mesh.mesh());
     }
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::path::Path;
+use ron;
+use context::Context;
+use fs;
+use geom::{Size, UvRect};
+use mesh::RMesh;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AnimationMode {
+    /// Starts over after the last frame until cancelled
+    #[default]
+    Loop,
+
+    /// Stops at the last frame
+    Once,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Animation {
+    /// Frame indices. Frames are numbered row by row, starting from the top-left one.
+    pub frames: Vec<usize>,
+
+    /// Frames per second
+    pub fps: f32,
+
+    #[serde(default)]
+    pub mode: AnimationMode,
+}
+
+/// Stored as a RON file next to the texture: `imp.ron` describes `imp.png`.
+///
+/// ```text
+/// (
+///     columns: 4,
+///     rows: 2,
+///     animations: {
+///         "idle": (frames: [0, 1], fps: 2.0),
+///         "death": (frames: [4, 5, 6, 7], fps: 8.0, mode: Once),
+///     },
+/// )
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpriteSheetDescriptor {
+    /// The texture is split into `columns * rows` frames of the same size
+    pub columns: usize,
+    pub rows: usize,
+
+    pub animations: HashMap<String, Animation>,
+}
+
+impl Default for SpriteSheetDescriptor {
+    fn default() -> Self {
+        Self {
+            columns: 1,
+            rows: 1,
+            animations: HashMap::new(),
+        }
+    }
+}
+
+impl SpriteSheetDescriptor {
+    fn validate(&self, path: &Path) {
+        let path = path.display();
+        assert!(
+            self.columns > 0 && self.rows > 0,
+            "'{}': a sprite sheet needs at least one frame",
+            path
+        );
+        let frame_count = self.columns * self.rows;
+        for (name, animation) in &self.animations {
+            assert!(
+                !animation.frames.is_empty(),
+                "'{}': animation '{}' has no frames",
+                path,
+                name
+            );
+            assert!(
+                animation.fps > 0.0,
+                "'{}': animation '{}' has a bad fps",
+                path,
+                name
+            );
+            for &frame in &animation.frames {
+                assert!(
+                    frame < frame_count,
+                    "'{}': animation '{}' has no frame {}",
+                    path,
+                    name,
+                    frame
+                );
+            }
+        }
+    }
+}
+
+/// Loads the descriptor of the texture or returns
+/// a one frame descriptor if there's no such file.
+pub(crate) fn load_descriptor(texture_path: &Path) -> SpriteSheetDescriptor {
+    let path = texture_path.with_extension("ron");
+    let descriptor = match fs::try_load(&path) {
+        Some(data) => {
+            let s = String::from_utf8(data).unwrap();
+            match ron::de::from_str(&s) {
+                Ok(descriptor) => descriptor,
+                Err(err) => panic!("Can`t parse '{}': {:?}", path.display(), err),
+            }
+        }
+        None => SpriteSheetDescriptor::default(),
+    };
+    descriptor.validate(&path);
+    descriptor
+}
+
+/// A texture split into frames plus the named animations of these frames
+#[derive(Clone, Debug)]
+pub struct SpriteSheet {
+    frames: Vec<RMesh>,
+    animations: HashMap<String, Animation>,
+}
+
+impl SpriteSheet {
+    /// Loads the texture and its optional descriptor (see `SpriteSheetDescriptor`).
+    ///
+    /// `size` is the size of a frame.
+    pub fn from_path<P: AsRef<Path>>(context: &mut Context, path: P, size: f32) -> Self {
+        let path = path.as_ref();
+        let descriptor = context.load_sprite_sheet_descriptor(path);
+        let texture = context.load_texture(path);
+        let size = Size { w: size, h: size };
+        let frame_w = 1.0 / descriptor.columns as f32;
+        let frame_h = 1.0 / descriptor.rows as f32;
+        let mut frames = Vec::new();
+        for row in 0..descriptor.rows {
+            for column in 0..descriptor.columns {
+                let uv = UvRect {
+                    x: column as f32 * frame_w,
+                    y: row as f32 * frame_h,
+                    w: frame_w,
+                    h: frame_h,
+                };
+                frames.push(RMesh::with_uv(context, texture.clone(), size, uv));
+            }
+        }
+        Self {
+            frames,
+            animations: descriptor.animations,
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub(crate) fn frame(&self, index: usize) -> &RMesh {
+        &self.frames[index]
+    }
+
+    pub fn animation(&self, name: &str) -> Option<&Animation> {
+        self.animations.get(name)
+    }
+
+    pub fn has_animation(&self, name: &str) -> bool {
+        self.animations.contains_key(name)
+    }
+}
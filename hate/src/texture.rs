@@ -3,12 +3,18 @@ use png;
 use gfx;
 use gfx_device_gl;
 use geom::Size;
-use pipeline::ColorFormat;
 use Context;
 
+/// A backend-specific texture handle
+#[derive(Debug, Clone)]
+pub enum RawTexture {
+    Gl(gfx::handle::ShaderResourceView<gfx_device_gl::Resources, [f32; 4]>),
+    Headless,
+}
+
 #[derive(Debug, Clone)]
 pub struct Texture {
-    pub raw: gfx::handle::ShaderResourceView<gfx_device_gl::Resources, [f32; 4]>,
+    pub raw: RawTexture,
     pub size: Size<i32>,
 }
 
@@ -21,20 +27,10 @@ pub fn load(context: &mut Context, data: &[u8]) -> Texture {
     };
     let mut buf = vec![0; info.buffer_size()];
     reader.next_frame(&mut buf).expect("Can't read the frame");
-    load_raw(context.factory_mut(), size, &buf)
+    load_raw(context, size, &buf)
 }
 
-pub fn load_raw<F>(factory: &mut F, size: Size<i32>, data: &[u8]) -> Texture
-where
-    F: gfx::Factory<gfx_device_gl::Resources>,
-{
-    let kind = gfx::texture::Kind::D2(
-        size.w as gfx::texture::Size,
-        size.h as gfx::texture::Size,
-        gfx::texture::AaMode::Single,
-    );
-    let (_, view) = factory
-        .create_texture_immutable_u8::<ColorFormat>(kind, &[data])
-        .unwrap();
-    Texture { raw: view, size }
+pub fn load_raw(context: &mut Context, size: Size<i32>, data: &[u8]) -> Texture {
+    let raw = context.backend_mut().create_texture(size, data);
+    Texture { raw, size }
 }
@@ -6,6 +6,9 @@ use screen::Screen;
 use context::Context;
 use settings::Settings;
 use screen_stack::Screens;
+use backend::{Backend, GlBackend, HeadlessBackend};
+use geom::Size;
+use audio::Audio;
 
 fn max_frame_time(context: &Context) -> Time {
     Time(1.0 / context.settings().max_fps)
@@ -18,9 +21,24 @@ pub struct Visualizer {
 }
 
 impl Visualizer {
+    /// Opens a window.
     pub fn new(settings: Settings) -> Self {
+        let audio = Audio::new(&settings);
+        Self::with_backend(settings, Box::new(GlBackend::new()), audio)
+    }
+
+    /// Doesn't open a window and draws nothing, but records
+    /// the draw calls (see `Context::draw_calls`). Plays no sounds.
+    ///
+    /// Use `push_screen` and `step` to drive it.
+    pub fn new_headless(settings: Settings, win_size: Size<i32>) -> Self {
+        let audio = Audio::new_null(&settings);
+        Self::with_backend(settings, Box::new(HeadlessBackend::new(win_size)), audio)
+    }
+
+    fn with_backend(settings: Settings, backend: Box<Backend>, audio: Audio) -> Self {
         let (tx, rx) = mpsc::channel();
-        let context = Context::new(tx, settings);
+        let context = Context::new(tx, settings, backend, audio);
         let screens = Screens::new(rx);
         let prev_frame_start = context.now();
         Self {
@@ -31,25 +49,37 @@ impl Visualizer {
     }
 
     pub fn run(&mut self, start_screen: Box<Screen>) {
-        let command_push_start_screen = screen::Command::Push(start_screen);
-        self.context.add_command(command_push_start_screen);
-        self.screens.handle_commands();
+        self.push_screen(start_screen);
         while self.is_running() {
             self.tick();
         }
     }
 
+    pub fn push_screen(&mut self, screen: Box<Screen>) {
+        self.context.add_command(screen::Command::Push(screen));
+        self.screens.handle_commands();
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
     pub fn context_mut(&mut self) -> &mut Context {
         &mut self.context
     }
 
-    fn tick(&mut self) {
-        let frame_start = self.context.now();
-        let dtime = frame_start.delta(self.prev_frame_start);
+    /// Draws one frame and handles the events without any frame rate limits.
+    pub fn step(&mut self, dtime: Time) {
         self.context.clear();
         self.screens.tick(&mut self.context, dtime);
         self.context.flush();
         self.handle_events();
+    }
+
+    fn tick(&mut self) {
+        let frame_start = self.context.now();
+        let dtime = frame_start.delta(self.prev_frame_start);
+        self.step(dtime);
         let frame_time = self.context.now().delta(frame_start);
         let remainder = max_frame_time(&self.context).delta(frame_time);
         if remainder > Time(0.0) {
@@ -64,7 +94,7 @@ impl Visualizer {
         }
     }
 
-    fn is_running(&self) -> bool {
+    pub fn is_running(&self) -> bool {
         !self.screens.should_close() && !self.context.should_close()
     }
 }
@@ -0,0 +1,141 @@
+//! Compares the storage backends.
+//!
+//! Run with `cargo bench -p rancor`.
+
+extern crate rancor;
+
+use std::time::{Duration, Instant};
+use rancor::{BTreeMapStorage, ComponentContainer, HashMapStorage, Join, SparseSetStorage,
+             Storage, VecStorage};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id {
+    index: u32,
+    generation: u32,
+}
+
+impl rancor::GenerationalId for Id {
+    fn new(index: u32, generation: u32) -> Self {
+        Id { index, generation }
+    }
+
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Pos(i32, i32);
+
+const OBJECTS_COUNT: u32 = 10_000;
+const ITERATIONS: u32 = 100;
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
+
+fn measure<F: FnMut()>(mut f: F) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    ms(start.elapsed()) / f64::from(ITERATIONS)
+}
+
+fn fill<S: Storage<Id, Pos>>(storage: &mut S) {
+    // Only every third object has the component
+    for index in 0..OBJECTS_COUNT {
+        if index % 3 == 0 {
+            let id = Id {
+                index,
+                generation: 0,
+            };
+            storage.insert(id, Pos(index as i32, 1));
+        }
+    }
+}
+
+fn bench<S: Storage<Id, Pos> + Clone>(name: &str) {
+    let insert = measure(|| {
+        let mut storage = S::default();
+        fill(&mut storage);
+    });
+    let mut storage = S::default();
+    fill(&mut storage);
+    let get = measure(|| {
+        let mut sum = 0;
+        for index in 0..OBJECTS_COUNT {
+            let id = Id {
+                index,
+                generation: 0,
+            };
+            if let Some(pos) = storage.get(id) {
+                sum += pos.0 + pos.1;
+            }
+        }
+        assert!(sum > 0);
+    });
+    let iter = measure(|| {
+        let sum: i32 = storage.iter().map(|(_, pos)| pos.0 + pos.1).sum();
+        assert!(sum > 0);
+    });
+    let remove = measure(|| {
+        let mut storage = storage.clone();
+        for index in 0..OBJECTS_COUNT {
+            let id = Id {
+                index,
+                generation: 0,
+            };
+            storage.remove(id);
+        }
+        assert!(storage.is_empty());
+    });
+    println!(
+        "{:<18} insert: {:>8.3}ms  get: {:>8.3}ms  iter: {:>8.3}ms  remove: {:>8.3}ms",
+        name, insert, get, iter, remove
+    );
+}
+
+fn bench_join() {
+    let mut hash_pos: ComponentContainer<Id, Pos> = ComponentContainer::new();
+    let mut hash_flag: ComponentContainer<Id, ()> = ComponentContainer::new();
+    let mut vec_pos: ComponentContainer<Id, Pos, VecStorage<Id, Pos>> = ComponentContainer::new();
+    let mut sparse_flag: ComponentContainer<Id, (), SparseSetStorage<Id, ()>> =
+        ComponentContainer::new();
+    for index in 0..OBJECTS_COUNT {
+        let id = Id {
+            index,
+            generation: 0,
+        };
+        hash_pos.insert(id, Pos(index as i32, 1));
+        vec_pos.insert(id, Pos(index as i32, 1));
+        if index % 10 == 0 {
+            hash_flag.insert(id, ());
+            sparse_flag.insert(id, ());
+        }
+    }
+    let expected_count = (OBJECTS_COUNT / 10) as usize;
+    let hash = measure(|| {
+        assert_eq!((&hash_pos, &hash_flag).join().count(), expected_count);
+    });
+    let dense = measure(|| {
+        assert_eq!((&vec_pos, &sparse_flag).join().count(), expected_count);
+    });
+    println!(
+        "join (10% match)   hash: {:>8.3}ms  vec + sparse set: {:>8.3}ms",
+        hash, dense
+    );
+}
+
+fn main() {
+    println!("{} objects, {} iterations", OBJECTS_COUNT, ITERATIONS);
+    bench::<HashMapStorage<Id, Pos>>("HashMapStorage");
+    bench::<BTreeMapStorage<Id, Pos>>("BTreeMapStorage");
+    bench::<VecStorage<Id, Pos>>("VecStorage");
+    bench::<SparseSetStorage<Id, Pos>>("SparseSetStorage");
+    bench_join();
+}
@@ -0,0 +1,91 @@
+/// Ids of the components inserted, modified and removed
+/// since the last `clear`.
+///
+/// Every id is in at most one of the lists: a component that was inserted
+/// and then modified is only "inserted", a component that was inserted and
+/// then removed is not mentioned at all.
+///
+/// Any mutable access counts as a modification.
+#[derive(Clone, Debug)]
+pub struct Changes<Id> {
+    inserted: Vec<Id>,
+    modified: Vec<Id>,
+    removed: Vec<Id>,
+}
+
+impl<Id: Copy + PartialEq> Changes<Id> {
+    pub fn new() -> Self {
+        Self {
+            inserted: Vec::new(),
+            modified: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+
+    pub fn inserted(&self) -> &[Id] {
+        &self.inserted
+    }
+
+    pub fn modified(&self) -> &[Id] {
+        &self.modified
+    }
+
+    pub fn removed(&self) -> &[Id] {
+        &self.removed
+    }
+
+    /// All the changed ids
+    pub fn ids<'a>(&'a self) -> Box<Iterator<Item = Id> + 'a> {
+        let iter = self.inserted.iter().chain(&self.modified).chain(&self.removed);
+        Box::new(iter.cloned())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.inserted.clear();
+        self.modified.clear();
+        self.removed.clear();
+    }
+
+    pub(crate) fn on_insert(&mut self, id: Id) {
+        if remove_item(&mut self.removed, id) {
+            // The component was replaced
+            self.modified.push(id);
+        } else {
+            self.inserted.push(id);
+        }
+    }
+
+    pub(crate) fn on_modify(&mut self, id: Id) {
+        if !self.inserted.contains(&id) && !self.modified.contains(&id) {
+            self.modified.push(id);
+        }
+    }
+
+    pub(crate) fn on_remove(&mut self, id: Id) {
+        if remove_item(&mut self.inserted, id) {
+            return;
+        }
+        remove_item(&mut self.modified, id);
+        self.removed.push(id);
+    }
+}
+
+impl<Id: Copy + PartialEq> Default for Changes<Id> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn remove_item<Id: PartialEq>(ids: &mut Vec<Id>, id: Id) -> bool {
+    match ids.iter().position(|other| *other == id) {
+        Some(index) => {
+            ids.remove(index);
+            true
+        }
+        None => false,
+    }
+}
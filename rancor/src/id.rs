@@ -0,0 +1,119 @@
+use std::marker::PhantomData;
+
+/// An object id made of a slot index and the slot's generation.
+///
+/// The generation is bumped every time the slot is freed,
+/// so a stale id never refers to a new object that reused the slot.
+pub trait GenerationalId: Copy {
+    fn new(index: u32, generation: u32) -> Self;
+    fn index(&self) -> u32;
+    fn generation(&self) -> u32;
+}
+
+/// Allocates generational ids and keeps track of the alive ones.
+#[derive(Clone, Debug, Default)]
+pub struct IdAllocator {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+}
+
+impl IdAllocator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn alloc<Id: GenerationalId>(&mut self) -> Id {
+        if let Some(index) = self.free.pop() {
+            self.alive[index as usize] = true;
+            Id::new(index, self.generations[index as usize])
+        } else {
+            let index = self.generations.len() as u32;
+            self.generations.push(0);
+            self.alive.push(true);
+            Id::new(index, 0)
+        }
+    }
+
+    /// Marks the id as dead and allows the reuse of its slot.
+    pub fn free<Id: GenerationalId>(&mut self, id: Id) {
+        assert!(self.is_alive(id));
+        let index = id.index() as usize;
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.push(id.index());
+    }
+
+    pub fn is_alive<Id: GenerationalId>(&self, id: Id) -> bool {
+        let index = id.index() as usize;
+        index < self.generations.len() && self.alive[index]
+            && self.generations[index] == id.generation()
+    }
+
+    /// Alive ids in the order of their indices
+    pub fn ids<Id: GenerationalId>(&self) -> AliveIdIter<Id> {
+        AliveIdIter {
+            allocator: self,
+            index: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn to_raw(&self) -> (&[u32], &[bool], &[u32]) {
+        (&self.generations, &self.alive, &self.free)
+    }
+
+    /// Checks that the free list holds only dead indices and each of them only once.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(
+        generations: Vec<u32>,
+        alive: Vec<bool>,
+        free: Vec<u32>,
+    ) -> Result<Self, String> {
+        if generations.len() != alive.len() {
+            return Err("lengths of generations and alive flags differ".into());
+        }
+        let mut is_free = vec![false; alive.len()];
+        for &index in &free {
+            match alive.get(index as usize) {
+                None => return Err(format!("free id index is out of range: {}", index)),
+                Some(&true) => return Err(format!("free id index is alive: {}", index)),
+                Some(&false) => {}
+            }
+            if is_free[index as usize] {
+                return Err(format!("duplicated free id index: {}", index));
+            }
+            is_free[index as usize] = true;
+        }
+        Ok(Self {
+            generations,
+            alive,
+            free,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AliveIdIter<'a, Id> {
+    allocator: &'a IdAllocator,
+    index: usize,
+    phantom: PhantomData<Id>,
+}
+
+impl<'a, Id: GenerationalId> Iterator for AliveIdIter<'a, Id> {
+    type Item = Id;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let allocator = self.allocator;
+        while self.index < allocator.generations.len() {
+            let index = self.index;
+            self.index += 1;
+            if allocator.alive[index] {
+                let generation = allocator.generations[index];
+                return Some(Id::new(index as u32, generation));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,183 @@
+//! Typed iteration over several containers at once.
+//!
+//! ```ignore
+//! use rancor::Join;
+//!
+//! for (id, pos, agent) in (&parts.pos, &parts.agent).join() {
+//!     // only objects that have both components
+//! }
+//!
+//! for (id, mut agent, belongs_to) in (&mut parts.agent, &parts.belongs_to).join() {
+//!     agent.moves = agent.base_moves; // only this marks the agent as modified
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use {Changes, ComponentContainer, Storage};
+
+pub trait Join {
+    type Item;
+    type Iter: Iterator<Item = Self::Item>;
+
+    fn join(self) -> Self::Iter;
+}
+
+/// Iterator over the objects that have all the joined components.
+///
+/// Walks over the ids of the smallest container
+/// and looks up the other containers.
+pub struct JoinIter<'a, Id: 'a, C> {
+    ids: Box<Iterator<Item = Id> + 'a>,
+    containers: C,
+}
+
+/// Iterator over the objects that have all the joined components,
+/// the first of which is mutable.
+///
+/// Walks over the mutable container with its own `iter_mut`
+/// and looks up the other containers.
+pub struct JoinMutIter<'a, Id: 'a, V: 'a, C> {
+    values: Box<Iterator<Item = (Id, &'a mut V)> + 'a>,
+    changes: Option<&'a RefCell<Changes<Id>>>,
+    containers: C,
+}
+
+/// A mutable reference to a component yielded by `JoinMutIter`.
+///
+/// The component is marked as modified only when it's actually
+/// mutably dereferenced.
+pub struct Mut<'a, Id: 'a, V: 'a> {
+    id: Id,
+    value: &'a mut V,
+    changes: Option<&'a RefCell<Changes<Id>>>,
+}
+
+impl<'a, Id: 'a, V: 'a> Deref for Mut<'a, Id, V> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        self.value
+    }
+}
+
+impl<'a, Id: Copy + PartialEq + 'a, V: 'a> DerefMut for Mut<'a, Id, V> {
+    fn deref_mut(&mut self) -> &mut V {
+        if let Some(changes) = self.changes {
+            changes.borrow_mut().on_modify(self.id);
+        }
+        self.value
+    }
+}
+
+macro_rules! impl_join {
+    ($($c:ident: $v:ident: $s:ident),+) => {
+        impl<'a, Id, $($v, $s),+> Join for ($(&'a ComponentContainer<Id, $v, $s>,)+)
+        where
+            Id: Copy + 'a,
+            $($v: 'a, $s: Storage<Id, $v> + 'a,)+
+        {
+            type Item = (Id, $(&'a $v),+);
+            type Iter = JoinIter<'a, Id, Self>;
+
+            fn join(self) -> Self::Iter {
+                let mut smallest: Option<(usize, Box<Iterator<Item = Id> + 'a>)> = None;
+                {
+                    let ($($c,)+) = self;
+                    $(
+                        let len = $c.data.len();
+                        if smallest.as_ref().map(|&(min, _)| len < min).unwrap_or(true) {
+                            smallest = Some((len, Box::new($c.data.iter().map(|(id, _)| id))));
+                        }
+                    )+
+                }
+                JoinIter {
+                    ids: smallest.unwrap().1,
+                    containers: self,
+                }
+            }
+        }
+
+        impl<'a, Id, $($v, $s),+> Iterator
+            for JoinIter<'a, Id, ($(&'a ComponentContainer<Id, $v, $s>,)+)>
+        where
+            Id: Copy + 'a,
+            $($v: 'a, $s: Storage<Id, $v> + 'a,)+
+        {
+            type Item = (Id, $(&'a $v),+);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let ($($c,)+) = self.containers;
+                while let Some(id) = self.ids.next() {
+                    if let ($(Some($c),)+) = ($($c.data.get(id),)+) {
+                        return Some((id, $($c),+));
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+macro_rules! impl_join_mut {
+    ($($c:ident: $v:ident: $s:ident),*) => {
+        impl<'a, Id, V, S, $($v, $s),*> Join
+            for (&'a mut ComponentContainer<Id, V, S>, $(&'a ComponentContainer<Id, $v, $s>,)*)
+        where
+            Id: Copy + PartialEq + 'a,
+            V: 'a,
+            S: Storage<Id, V> + 'a,
+            $($v: 'a, $s: Storage<Id, $v> + 'a,)*
+        {
+            type Item = (Id, Mut<'a, Id, V>, $(&'a $v),*);
+            type Iter = JoinMutIter<'a, Id, V, ($(&'a ComponentContainer<Id, $v, $s>,)*)>;
+
+            fn join(self) -> Self::Iter {
+                let (first, $($c,)*) = self;
+                let ComponentContainer {
+                    ref mut data,
+                    ref changes,
+                    ..
+                } = *first;
+                JoinMutIter {
+                    values: data.iter_mut(),
+                    changes: changes.as_ref(),
+                    containers: ($($c,)*),
+                }
+            }
+        }
+
+        impl<'a, Id, V, $($v, $s),*> Iterator
+            for JoinMutIter<'a, Id, V, ($(&'a ComponentContainer<Id, $v, $s>,)*)>
+        where
+            Id: Copy + PartialEq + 'a,
+            V: 'a,
+            $($v: 'a, $s: Storage<Id, $v> + 'a,)*
+        {
+            type Item = (Id, Mut<'a, Id, V>, $(&'a $v),*);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let ($($c,)*) = self.containers;
+                while let Some((id, value)) = self.values.next() {
+                    if let ($(Some($c),)*) = ($($c.data.get(id),)*) {
+                        let value = Mut {
+                            id,
+                            value,
+                            changes: self.changes,
+                        };
+                        return Some((id, value, $($c),*));
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+impl_join!(a: A: SA, b: B: SB);
+impl_join!(a: A: SA, b: B: SB, c: C: SC);
+impl_join!(a: A: SA, b: B: SB, c: C: SC, d: D: SD);
+
+impl_join_mut!(b: B: SB);
+impl_join_mut!(b: B: SB, c: C: SC);
+impl_join_mut!(b: B: SB, c: C: SC, d: D: SD);
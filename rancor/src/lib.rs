@@ -1,22 +1,74 @@
 // TODO: add debug!() logs everywhere
 
-use std::collections::{hash_map, HashMap};
-use std::hash::Hash;
+#[cfg(feature = "serde")]
+pub extern crate serde;
+
+use std::cell::{Ref, RefCell};
+use std::marker::PhantomData;
+
+mod changes;
+mod id;
+mod join;
+mod storage;
+
+pub use changes::Changes;
+pub use id::{AliveIdIter, GenerationalId, IdAllocator};
+pub use join::{Join, JoinIter, JoinMutIter, Mut};
+pub use storage::{BTreeMapStorage, HashMapStorage, SparseSetStorage, Storage, VecStorage};
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde_impls;
+
+/// Does nothing: the "serde" feature is disabled.
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! rancor_storage_serde {
+    ($($t:tt)*) => {};
+}
 
 #[derive(Debug, Clone)]
-pub struct ComponentContainer<Id: Hash + Eq, V> {
-    data: HashMap<Id, V>,
+pub struct ComponentContainer<Id, V, S = HashMapStorage<Id, V>> {
+    data: S,
+    changes: Option<RefCell<Changes<Id>>>,
+    phantom: PhantomData<(Id, V)>,
 }
 
-impl<Id: Hash + Eq + Copy, V: Clone> ComponentContainer<Id, V> {
+impl<Id: Copy + PartialEq, V: Clone, S: Storage<Id, V>> ComponentContainer<Id, V, S> {
     pub fn new() -> Self {
         Self {
-            data: HashMap::new(),
+            data: S::default(),
+            changes: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Starts recording the changes (see `changes`). Disabled by default.
+    pub fn enable_change_tracking(&mut self) {
+        if self.changes.is_none() {
+            self.changes = Some(RefCell::new(Changes::new()));
+        }
+    }
+
+    pub fn is_change_tracking_enabled(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    /// Changes since the last `clear_changes` call
+    /// or `None` if the tracking is disabled.
+    pub fn changes(&self) -> Option<Ref<Changes<Id>>> {
+        self.changes.as_ref().map(|changes| changes.borrow())
+    }
+
+    pub fn clear_changes(&mut self) {
+        if let Some(ref mut changes) = self.changes {
+            changes.get_mut().clear();
         }
     }
 
     pub fn get_opt(&self, id: Id) -> Option<&V> {
-        self.data.get(&id)
+        self.data.get(id)
     }
 
     pub fn get(&self, id: Id) -> &V {
@@ -24,7 +76,13 @@ impl<Id: Hash + Eq + Copy, V: Clone> ComponentContainer<Id, V> {
     }
 
     pub fn get_opt_mut(&mut self, id: Id) -> Option<&mut V> {
-        self.data.get_mut(&id)
+        let value = self.data.get_mut(id);
+        if value.is_some() {
+            if let Some(ref mut changes) = self.changes {
+                changes.get_mut().on_modify(id);
+            }
+        }
+        value
     }
 
     pub fn get_mut(&mut self, id: Id) -> &mut V {
@@ -34,53 +92,181 @@ impl<Id: Hash + Eq + Copy, V: Clone> ComponentContainer<Id, V> {
     pub fn insert(&mut self, id: Id, data: V) {
         assert!(self.get_opt(id).is_none());
         self.data.insert(id, data);
+        if let Some(ref mut changes) = self.changes {
+            changes.get_mut().on_insert(id);
+        }
     }
 
     pub fn remove(&mut self, id: Id) {
         assert!(self.get_opt(id).is_some());
-        self.data.remove(&id);
+        self.data.remove(id);
+        if let Some(ref mut changes) = self.changes {
+            changes.get_mut().on_remove(id);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
     }
 
     pub fn ids(&self) -> IdIter<Id, V> {
-        IdIter::new(&self.data)
+        IdIter {
+            iter: self.data.iter(),
+        }
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct IdIter<'a, Id: 'a, V: 'a> {
-    iter: hash_map::Iter<'a, Id, V>,
+impl<Id: Copy + PartialEq, V: Clone, S: Storage<Id, V>> Default for ComponentContainer<Id, V, S> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl<'a, Id: Eq + Hash + Clone + 'a, V: 'a> IdIter<'a, Id, V> {
-    pub fn new(map: &'a HashMap<Id, V>) -> Self {
-        Self { iter: map.iter() }
-    }
+pub struct IdIter<'a, Id: 'a, V: 'a> {
+    iter: Box<Iterator<Item = (Id, &'a V)> + 'a>,
 }
 
 impl<'a, Id: Copy + 'a, V> Iterator for IdIter<'a, Id, V> {
     type Item = Id;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some((&id, _)) = self.iter.next() {
-            Some(id)
-        } else {
-            None
-        }
+        self.iter.next().map(|(id, _)| id)
     }
 }
 
+/// Generates a struct with a container for every component.
+///
+/// Containers use `HashMapStorage` by default, another backend
+/// can be selected with `=>` (the name must be in scope):
+///
+/// ```ignore
+/// rancor_storage!(Parts<ObjId>: {
+///     pos: Pos => VecStorage,
+///     flag: Flag => SparseSetStorage,
+///     name: Name,
+/// });
+/// ```
+///
+/// With `with enum` it also generates an enum with a variant
+/// for every component, a fieldless enum of their kinds
+/// and the dispatch methods (`insert_component`, `remove_component`
+/// and `components_of`):
+///
+/// ```ignore
+/// rancor_storage!(Parts<ObjId>: {
+///     pos: Pos(component::Pos) => VecStorage,
+///     name: Name(component::Name),
+/// } with #[derive(Clone, Debug)] enum Component / ComponentKind);
+/// ```
 #[macro_export]
 macro_rules! rancor_storage {
-    ($struct_name:ident<$id_type:ty>: { $($component:ident: $t:ty,)* } ) => {
-        use std::collections::HashMap;
+    ($struct_name:ident<$id_type:ty>: {
+        $($component:ident: $variant:ident($t:ty) $(=> $storage:ident)*,)*
+    } with $(#[$attr:meta])* enum $enum_name:ident / $kind_name:ident) => {
+        rancor_storage!($struct_name<$id_type>: {
+            $($component: $t $(=> $storage)*,)*
+        });
 
+        $(#[$attr])*
+        pub enum $enum_name {
+            $(
+                $variant($t),
+            )*
+        }
+
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum $kind_name {
+            $(
+                $variant,
+            )*
+        }
+
+        #[allow(dead_code)]
+        impl $kind_name {
+            /// Name of the container that stores components of this kind
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    $(
+                        $kind_name::$variant => stringify!($component),
+                    )*
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl $enum_name {
+            pub fn kind(&self) -> $kind_name {
+                match *self {
+                    $(
+                        $enum_name::$variant(_) => $kind_name::$variant,
+                    )*
+                }
+            }
+
+            /// Name of the container that stores this component
+            pub fn name(&self) -> &'static str {
+                self.kind().name()
+            }
+        }
+
+        #[allow(dead_code)]
+        impl $struct_name {
+            pub fn insert_component(&mut self, id: $id_type, component: $enum_name) {
+                match component {
+                    $(
+                        $enum_name::$variant(c) => self.$component.insert(id, c),
+                    )*
+                }
+            }
+
+            /// Removes the object's component of the given kind and returns it.
+            pub fn remove_component(
+                &mut self,
+                id: $id_type,
+                kind: $kind_name,
+            ) -> Option<$enum_name> {
+                match kind {
+                    $(
+                        $kind_name::$variant => {
+                            let component = self.$component.get_opt(id).cloned();
+                            if component.is_some() {
+                                self.$component.remove(id);
+                            }
+                            component.map($enum_name::$variant)
+                        }
+                    )*
+                }
+            }
+
+            /// All the object's components
+            pub fn components_of(&self, id: $id_type) -> Vec<$enum_name> {
+                let mut components = Vec::new();
+                $(
+                    if let Some(c) = self.$component.get_opt(id) {
+                        components.push($enum_name::$variant(c.clone()));
+                    }
+                )*
+                components
+            }
+        }
+    };
+    ($struct_name:ident<$id_type:ty>: {
+        $($component:ident: $t:ty $(=> $storage:ident)*,)*
+    }) => {
         #[derive(Clone, Debug)]
         pub struct $struct_name {
             $(
-                pub $component: $crate::ComponentContainer<$id_type, $t>,
+                pub $component: $crate::ComponentContainer<
+                    $id_type,
+                    $t
+                    $(, $storage<$id_type, $t>)*
+                >,
             )*
-            next_obj_id: $id_type,
-            ids: HashMap<$id_type, ()>,
+            ids: $crate::IdAllocator,
         }
 
         #[allow(dead_code)]
@@ -90,37 +276,51 @@ macro_rules! rancor_storage {
                     $(
                         $component: $crate::ComponentContainer::new(),
                     )*
-                    next_obj_id: Default::default(),
-                    ids: HashMap::new(),
+                    ids: $crate::IdAllocator::new(),
                 }
             }
 
             pub fn alloc_id(&mut self) -> $id_type {
-                let id = self.next_obj_id;
-                self.next_obj_id.0 += 1;
-                self.ids.insert(id, ());
-                id
+                self.ids.alloc()
+            }
+
+            /// Alive ids
+            pub fn ids(&self) -> $crate::AliveIdIter<$id_type> {
+                self.ids.ids()
             }
 
-            pub fn ids(&self) -> $crate::IdIter<$id_type, ()> {
-                $crate::IdIter::new(&self.ids)
+            /// Returns `false` for ids of removed objects
+            /// even if their slots were reused.
+            pub fn is_alive(&self, id: $id_type) -> bool {
+                self.ids.is_alive(id)
             }
 
-            pub fn is_exist(&self, id: $id_type) -> bool {
+            /// Starts recording the changes in all the containers.
+            pub fn enable_change_tracking(&mut self) {
                 $(
-                    if self.$component.get_opt(id).is_some() {
-                        return true;
-                    }
+                    self.$component.enable_change_tracking();
+                )*
+            }
+
+            /// `true` if all the containers record their changes.
+            pub fn is_change_tracking_enabled(&self) -> bool {
+                true $(&& self.$component.is_change_tracking_enabled())*
+            }
+
+            pub fn clear_changes(&mut self) {
+                $(
+                    self.$component.clear_changes();
                 )*
-                false
             }
 
+            /// Removes all the object's components and frees its id.
             pub fn remove(&mut self, id: $id_type) {
                 $(
                     if self.$component.get_opt(id).is_some() {
                         self.$component.remove(id);
                     }
                 )*
+                self.ids.free(id);
             }
 
             pub fn debug_string(&self, id: $id_type) -> String {
@@ -133,5 +333,7 @@ macro_rules! rancor_storage {
                 s
             }
         }
+
+        rancor_storage_serde!($struct_name<$id_type>: { $($component: $t,)* });
     }
 }
@@ -0,0 +1,178 @@
+//! Optional serde support (the "serde" feature).
+//!
+//! Containers are serialized as sequences of `(id, component)` pairs
+//! sorted by id, so the output doesn't depend on `HashMap`'s iteration order.
+
+use std::marker::PhantomData;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use {ComponentContainer, IdAllocator, Storage};
+
+impl<Id, V, St> Serialize for ComponentContainer<Id, V, St>
+where
+    Id: Ord + Copy + Serialize,
+    V: Serialize,
+    St: Storage<Id, V>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut items: Vec<(Id, &V)> = self.data.iter().collect();
+        items.sort_by_key(|&(id, _)| id);
+        serializer.collect_seq(items)
+    }
+}
+
+/// The change tracking is disabled: it's restored
+/// by the storage struct (see `rancor_storage_serde!`).
+impl<'de, Id, V, St> Deserialize<'de> for ComponentContainer<Id, V, St>
+where
+    Id: Copy + PartialEq + Deserialize<'de>,
+    V: Deserialize<'de>,
+    St: Storage<Id, V>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let items: Vec<(Id, V)> = Deserialize::deserialize(deserializer)?;
+        let mut data = St::default();
+        for (id, value) in items {
+            data.insert(id, value);
+        }
+        Ok(Self {
+            data,
+            changes: None,
+            phantom: PhantomData,
+        })
+    }
+}
+
+/// Stored as a `(generations, alive, free)` tuple.
+impl Serialize for IdAllocator {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_raw().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for IdAllocator {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let (generations, alive, free) = Deserialize::deserialize(deserializer)?;
+        IdAllocator::from_raw(generations, alive, free).map_err(D::Error::custom)
+    }
+}
+
+/// A struct field's name that can be deserialized from an identifier.
+#[doc(hidden)]
+pub struct FieldName(pub String);
+
+impl<'de> Deserialize<'de> for FieldName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use std::{fmt, str};
+        use serde::de::{self, Visitor};
+
+        struct FieldNameVisitor;
+
+        impl<'de> Visitor<'de> for FieldNameVisitor {
+            type Value = FieldName;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a field name")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<FieldName, E> {
+                Ok(FieldName(value.into()))
+            }
+
+            // Some formats (like RON) give out identifiers as bytes
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<FieldName, E> {
+                match str::from_utf8(value) {
+                    Ok(value) => self.visit_str(value),
+                    Err(_) => Err(de::Error::invalid_value(de::Unexpected::Bytes(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(FieldNameVisitor)
+    }
+}
+
+/// Implements `Serialize` and `Deserialize` for a `rancor_storage!`-generated struct.
+///
+/// Missing components are deserialized as empty containers
+/// and unknown fields are ignored.
+///
+/// The recorded changes are not stored, but the change tracking
+/// is enabled again if it was enabled in the serialized storage.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! rancor_storage_serde {
+    ($struct_name:ident<$id_type:ty>: { $($component:ident: $t:ty,)* } ) => {
+        impl $crate::serde::Serialize for $struct_name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: $crate::serde::Serializer,
+            {
+                use $crate::serde::ser::SerializeStruct;
+                const FIELDS: &[&str] = &["ids", "change_tracking", $(stringify!($component),)*];
+                let name = stringify!($struct_name);
+                let mut state = serializer.serialize_struct(name, FIELDS.len())?;
+                state.serialize_field("ids", &self.ids)?;
+                let is_tracked = self.is_change_tracking_enabled();
+                state.serialize_field("change_tracking", &is_tracked)?;
+                $(
+                    state.serialize_field(stringify!($component), &self.$component)?;
+                )*
+                state.end()
+            }
+        }
+
+        impl<'de> $crate::serde::Deserialize<'de> for $struct_name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: $crate::serde::Deserializer<'de>,
+            {
+                use std::fmt;
+                use $crate::serde::de::{self, MapAccess, Visitor};
+
+                const FIELDS: &[&str] = &["ids", "change_tracking", $(stringify!($component),)*];
+
+                struct StorageVisitor;
+
+                impl<'de> Visitor<'de> for StorageVisitor {
+                    type Value = $struct_name;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        write!(f, "struct {}", stringify!($struct_name))
+                    }
+
+                    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: MapAccess<'de>,
+                    {
+                        let mut storage = $struct_name::new();
+                        let mut ids = None;
+                        let mut is_tracked = false;
+                        while let Some(key) = map.next_key::<$crate::serde_impls::FieldName>()? {
+                            match key.0.as_str() {
+                                "ids" => ids = Some(map.next_value()?),
+                                "change_tracking" => is_tracked = map.next_value()?,
+                                $(
+                                    stringify!($component) => {
+                                        storage.$component = map.next_value()?;
+                                    }
+                                )*
+                                _ => {
+                                    map.next_value::<de::IgnoredAny>()?;
+                                }
+                            }
+                        }
+                        storage.ids = ids.ok_or_else(|| de::Error::missing_field("ids"))?;
+                        if is_tracked {
+                            storage.enable_change_tracking();
+                        }
+                        Ok(storage)
+                    }
+                }
+
+                let name = stringify!($struct_name);
+                deserializer.deserialize_struct(name, FIELDS, StorageVisitor)
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Storage backends for `ComponentContainer`.
+//!
+//! - `HashMapStorage` - the default one; iteration order is random;
+//! - `VecStorage` - a dense vector indexed by id, good for common components;
+//! - `SparseSetStorage` - a packed vector plus an index, good for rare components;
+//! - `BTreeMapStorage` - an ordered map, works with any `Ord` id.
+//!
+//! All the backends except `HashMapStorage` iterate in a deterministic order.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use GenerationalId;
+
+pub trait Storage<Id, V>: Default {
+    fn get(&self, id: Id) -> Option<&V>;
+
+    fn get_mut(&mut self, id: Id) -> Option<&mut V>;
+
+    /// Returns the old value if there was one.
+    fn insert(&mut self, id: Id, value: V) -> Option<V>;
+
+    fn remove(&mut self, id: Id) -> Option<V>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn iter<'a>(&'a self) -> Box<Iterator<Item = (Id, &'a V)> + 'a>;
+
+    fn iter_mut<'a>(&'a mut self) -> Box<Iterator<Item = (Id, &'a mut V)> + 'a>;
+}
+
+#[derive(Clone, Debug)]
+pub struct HashMapStorage<Id: Hash + Eq, V> {
+    data: HashMap<Id, V>,
+}
+
+impl<Id: Hash + Eq, V> Default for HashMapStorage<Id, V> {
+    fn default() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+}
+
+impl<Id: Hash + Eq + Copy, V> Storage<Id, V> for HashMapStorage<Id, V> {
+    fn get(&self, id: Id) -> Option<&V> {
+        self.data.get(&id)
+    }
+
+    fn get_mut(&mut self, id: Id) -> Option<&mut V> {
+        self.data.get_mut(&id)
+    }
+
+    fn insert(&mut self, id: Id, value: V) -> Option<V> {
+        self.data.insert(id, value)
+    }
+
+    fn remove(&mut self, id: Id) -> Option<V> {
+        self.data.remove(&id)
+    }
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn iter<'a>(&'a self) -> Box<Iterator<Item = (Id, &'a V)> + 'a> {
+        Box::new(self.data.iter().map(|(&id, value)| (id, value)))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<Iterator<Item = (Id, &'a mut V)> + 'a> {
+        Box::new(self.data.iter_mut().map(|(&id, value)| (id, value)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BTreeMapStorage<Id: Ord, V> {
+    data: BTreeMap<Id, V>,
+}
+
+impl<Id: Ord, V> Default for BTreeMapStorage<Id, V> {
+    fn default() -> Self {
+        Self {
+            data: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Id: Ord + Copy, V> Storage<Id, V> for BTreeMapStorage<Id, V> {
+    fn get(&self, id: Id) -> Option<&V> {
+        self.data.get(&id)
+    }
+
+    fn get_mut(&mut self, id: Id) -> Option<&mut V> {
+        self.data.get_mut(&id)
+    }
+
+    fn insert(&mut self, id: Id, value: V) -> Option<V> {
+        self.data.insert(id, value)
+    }
+
+    fn remove(&mut self, id: Id) -> Option<V> {
+        self.data.remove(&id)
+    }
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn iter<'a>(&'a self) -> Box<Iterator<Item = (Id, &'a V)> + 'a> {
+        Box::new(self.data.iter().map(|(&id, value)| (id, value)))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<Iterator<Item = (Id, &'a mut V)> + 'a> {
+        Box::new(self.data.iter_mut().map(|(&id, value)| (id, value)))
+    }
+}
+
+/// Slot `i` holds the component of the object with index `i`.
+///
+/// Iterates in the order of indices.
+#[derive(Clone, Debug)]
+pub struct VecStorage<Id, V> {
+    slots: Vec<Option<(Id, V)>>,
+    len: usize,
+}
+
+impl<Id, V> Default for VecStorage<Id, V> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<Id: GenerationalId + Eq, V> Storage<Id, V> for VecStorage<Id, V> {
+    fn get(&self, id: Id) -> Option<&V> {
+        match self.slots.get(id.index() as usize) {
+            Some(&Some((slot_id, ref value))) if slot_id == id => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, id: Id) -> Option<&mut V> {
+        match self.slots.get_mut(id.index() as usize) {
+            Some(&mut Some((slot_id, ref mut value))) if slot_id == id => Some(value),
+            _ => None,
+        }
+    }
+
+    fn insert(&mut self, id: Id, value: V) -> Option<V> {
+        let index = id.index() as usize;
+        while self.slots.len() <= index {
+            self.slots.push(None);
+        }
+        let old = self.slots[index].take();
+        self.slots[index] = Some((id, value));
+        match old {
+            // A stale generation is just overwritten
+            Some((old_id, old_value)) => if old_id == id {
+                Some(old_value)
+            } else {
+                None
+            },
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    fn remove(&mut self, id: Id) -> Option<V> {
+        self.get(id)?;
+        self.len -= 1;
+        self.slots[id.index() as usize].take().map(|(_, value)| value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter<'a>(&'a self) -> Box<Iterator<Item = (Id, &'a V)> + 'a> {
+        Box::new(self.slots.iter().filter_map(|slot| match *slot {
+            Some((id, ref value)) => Some((id, value)),
+            None => None,
+        }))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<Iterator<Item = (Id, &'a mut V)> + 'a> {
+        Box::new(self.slots.iter_mut().filter_map(|slot| match *slot {
+            Some((id, ref mut value)) => Some((id, value)),
+            None => None,
+        }))
+    }
+}
+
+/// Components are packed in a dense vector,
+/// `sparse[index]` is the position of the object's component in it.
+///
+/// Iterates in the order of insertion (removal moves the last
+/// component to the freed place).
+#[derive(Clone, Debug)]
+pub struct SparseSetStorage<Id, V> {
+    sparse: Vec<Option<usize>>,
+    dense: Vec<(Id, V)>,
+}
+
+impl<Id, V> Default for SparseSetStorage<Id, V> {
+    fn default() -> Self {
+        Self {
+            sparse: Vec::new(),
+            dense: Vec::new(),
+        }
+    }
+}
+
+impl<Id: GenerationalId + Eq, V> SparseSetStorage<Id, V> {
+    fn position(&self, id: Id) -> Option<usize> {
+        match self.sparse.get(id.index() as usize) {
+            Some(&Some(i)) if self.dense[i].0 == id => Some(i),
+            _ => None,
+        }
+    }
+}
+
+impl<Id: GenerationalId + Eq, V> Storage<Id, V> for SparseSetStorage<Id, V> {
+    fn get(&self, id: Id) -> Option<&V> {
+        self.position(id).map(|i| &self.dense[i].1)
+    }
+
+    fn get_mut(&mut self, id: Id) -> Option<&mut V> {
+        match self.position(id) {
+            Some(i) => Some(&mut self.dense[i].1),
+            None => None,
+        }
+    }
+
+    fn insert(&mut self, id: Id, value: V) -> Option<V> {
+        let index = id.index() as usize;
+        while self.sparse.len() <= index {
+            self.sparse.push(None);
+        }
+        if let Some(i) = self.sparse[index] {
+            let old_id = self.dense[i].0;
+            let old_value = ::std::mem::replace(&mut self.dense[i], (id, value)).1;
+            // A stale generation is just overwritten
+            return if old_id == id { Some(old_value) } else { None };
+        }
+        self.sparse[index] = Some(self.dense.len());
+        self.dense.push((id, value));
+        None
+    }
+
+    fn remove(&mut self, id: Id) -> Option<V> {
+        let i = self.position(id)?;
+        self.sparse[id.index() as usize] = None;
+        let (_, value) = self.dense.swap_remove(i);
+        if i < self.dense.len() {
+            let moved_index = self.dense[i].0.index() as usize;
+            self.sparse[moved_index] = Some(i);
+        }
+        Some(value)
+    }
+
+    fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    fn iter<'a>(&'a self) -> Box<Iterator<Item = (Id, &'a V)> + 'a> {
+        Box::new(self.dense.iter().map(|&(id, ref value)| (id, value)))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<Iterator<Item = (Id, &'a mut V)> + 'a> {
+        Box::new(self.dense.iter_mut().map(|&mut (id, ref mut value)| (id, value)))
+    }
+}
@@ -0,0 +1,84 @@
+#[macro_use]
+extern crate rancor;
+
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+
+use rancor::VecStorage;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id {
+    index: u32,
+    generation: u32,
+}
+
+impl rancor::GenerationalId for Id {
+    fn new(index: u32, generation: u32) -> Self {
+        Id { index, generation }
+    }
+
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pos(i32);
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Name(String);
+
+rancor_storage!(Parts<Id>: {
+    pos: Pos(Pos) => VecStorage,
+    name: Name(Name),
+} with #[derive(Clone, Debug, PartialEq)] enum Component / ComponentKind);
+
+#[test]
+fn kinds_and_names() {
+    let component = Component::Name(Name("imp".into()));
+    assert_eq!(component.kind(), ComponentKind::Name);
+    assert_eq!(component.name(), "name");
+    assert_eq!(ComponentKind::Pos.name(), "pos");
+}
+
+#[test]
+fn insert_and_remove_by_kind() {
+    let mut parts = Parts::new();
+    let id = parts.alloc_id();
+    parts.insert_component(id, Component::Pos(Pos(1)));
+    parts.insert_component(id, Component::Name(Name("imp".into())));
+    assert_eq!(parts.pos.get(id), &Pos(1));
+    assert_eq!(
+        parts.remove_component(id, ComponentKind::Pos),
+        Some(Component::Pos(Pos(1)))
+    );
+    assert!(parts.pos.get_opt(id).is_none());
+    assert_eq!(parts.remove_component(id, ComponentKind::Pos), None);
+    assert_eq!(parts.name.get(id), &Name("imp".into()));
+}
+
+#[test]
+fn components_of() {
+    let mut parts = Parts::new();
+    let id = parts.alloc_id();
+    let other_id = parts.alloc_id();
+    parts.insert_component(id, Component::Name(Name("imp".into())));
+    parts.insert_component(id, Component::Pos(Pos(2)));
+    parts.insert_component(other_id, Component::Pos(Pos(3)));
+    assert_eq!(
+        parts.components_of(id),
+        vec![
+            Component::Pos(Pos(2)),
+            Component::Name(Name("imp".into())),
+        ]
+    );
+    assert_eq!(parts.components_of(other_id), vec![Component::Pos(Pos(3))]);
+}
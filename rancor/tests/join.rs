@@ -0,0 +1,137 @@
+extern crate rancor;
+
+use rancor::{ComponentContainer, GenerationalId, Join, SparseSetStorage, VecStorage};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id {
+    index: u32,
+    generation: u32,
+}
+
+impl GenerationalId for Id {
+    fn new(index: u32, generation: u32) -> Self {
+        Id { index, generation }
+    }
+
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pos(i32);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Flag;
+
+type Positions = ComponentContainer<Id, Pos, VecStorage<Id, Pos>>;
+type Flags = ComponentContainer<Id, Flag, SparseSetStorage<Id, Flag>>;
+
+/// Ten objects with positions, every third one is flagged.
+fn make_containers() -> (Positions, Flags, Vec<Id>) {
+    let mut positions = Positions::new();
+    let mut flags = Flags::new();
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let id = Id::new(i, 0);
+        positions.insert(id, Pos(i as i32));
+        if i % 3 == 0 {
+            flags.insert(id, Flag);
+        }
+        ids.push(id);
+    }
+    positions.enable_change_tracking();
+    flags.enable_change_tracking();
+    (positions, flags, ids)
+}
+
+#[test]
+fn mutable_side_is_bigger() {
+    let (mut positions, flags, ids) = make_containers();
+    let mut joined = Vec::new();
+    for (id, mut pos, _) in (&mut positions, &flags).join() {
+        pos.0 += 100;
+        joined.push(id);
+    }
+    joined.sort();
+    assert_eq!(joined, vec![ids[0], ids[3], ids[6], ids[9]]);
+    assert_eq!(positions.get(ids[3]), &Pos(103));
+    assert_eq!(positions.get(ids[4]), &Pos(4));
+}
+
+#[test]
+fn mutable_side_is_smaller() {
+    let (positions, mut flags, ids) = make_containers();
+    let mut joined = Vec::new();
+    for (id, _, _) in (&mut flags, &positions).join() {
+        joined.push(id);
+    }
+    joined.sort();
+    assert_eq!(joined, vec![ids[0], ids[3], ids[6], ids[9]]);
+}
+
+#[test]
+fn only_mutated_components_are_modified() {
+    let (mut positions, flags, ids) = make_containers();
+    for (_, mut pos, _) in (&mut positions, &flags).join() {
+        if pos.0 > 5 {
+            pos.0 = 0;
+        }
+    }
+    let changes = positions.changes().unwrap();
+    let mut modified = changes.modified().to_vec();
+    modified.sort();
+    assert_eq!(modified, vec![ids[6], ids[9]]);
+    assert!(changes.inserted().is_empty());
+    assert!(changes.removed().is_empty());
+}
+
+#[test]
+fn reading_does_not_modify() {
+    let (mut positions, flags, _) = make_containers();
+    let mut sum = 0;
+    for (_, pos, _) in (&mut positions, &flags).join() {
+        sum += pos.0;
+    }
+    assert_eq!(sum, 18);
+    assert!(positions.changes().unwrap().is_empty());
+}
+
+#[test]
+fn untracked_container() {
+    let mut positions = Positions::new();
+    let mut flags = Flags::new();
+    let id = Id::new(0, 0);
+    positions.insert(id, Pos(1));
+    flags.insert(id, Flag);
+    for (_, mut pos, _) in (&mut positions, &flags).join() {
+        pos.0 = 2;
+    }
+    assert_eq!(positions.get(id), &Pos(2));
+    assert!(positions.changes().is_none());
+}
+
+/// All the yielded references are alive at once
+/// (run with `cargo miri test` to check the aliasing).
+#[test]
+fn yielded_values_can_be_held_together() {
+    let (mut positions, flags, ids) = make_containers();
+    let mut values: Vec<_> = (&mut positions, &flags)
+        .join()
+        .map(|(_, pos, _)| pos)
+        .collect();
+    assert_eq!(values.len(), 4);
+    for pos in &mut values {
+        pos.0 = -pos.0;
+    }
+    drop(values);
+    assert_eq!(positions.get(ids[3]), &Pos(-3));
+    assert_eq!(positions.get(ids[4]), &Pos(4));
+    let mut modified = positions.changes().unwrap().modified().to_vec();
+    modified.sort();
+    assert_eq!(modified, vec![ids[0], ids[3], ids[6], ids[9]]);
+}
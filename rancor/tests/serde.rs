@@ -0,0 +1,177 @@
+#[macro_use]
+extern crate rancor;
+
+#[macro_use]
+extern crate serde_derive;
+
+extern crate ron;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id {
+    index: u32,
+    generation: u32,
+}
+
+impl rancor::GenerationalId for Id {
+    fn new(index: u32, generation: u32) -> Self {
+        Id { index, generation }
+    }
+
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Pos(i32, i32);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Name(String);
+
+// Two storages in one module must not clash
+mod parts {
+    use {Id, Name, Pos};
+
+    rancor_storage!(Parts<Id>: {
+        pos: Pos,
+        name: Name,
+    });
+
+    rancor_storage!(Names<Id>: {
+        name: Name,
+    });
+}
+
+use parts::{Names, Parts};
+
+fn check_equal(a: &Parts, b: &Parts) {
+    let a_ids: Vec<Id> = a.ids().collect();
+    let b_ids: Vec<Id> = b.ids().collect();
+    assert_eq!(a_ids, b_ids);
+    for &id in &a_ids {
+        assert_eq!(a.pos.get_opt(id), b.pos.get_opt(id));
+        assert_eq!(a.name.get_opt(id), b.name.get_opt(id));
+    }
+}
+
+/// Ten objects, every third one has a name. The first one is removed.
+fn make_parts() -> (Parts, Id) {
+    let mut parts = Parts::new();
+    for i in 0..10 {
+        let id = parts.alloc_id();
+        parts.pos.insert(id, Pos(i, -i));
+        if i % 3 == 0 {
+            parts.name.insert(id, Name(format!("object #{}", i)));
+        }
+    }
+    let removed_id = parts.ids().next().unwrap();
+    parts.remove(removed_id);
+    (parts, removed_id)
+}
+
+#[test]
+fn round_trip() {
+    let (parts, _) = make_parts();
+    let s = ron::ser::to_string(&parts).unwrap();
+    let restored: Parts = ron::de::from_str(&s).unwrap();
+    check_equal(&parts, &restored);
+}
+
+#[test]
+fn stable_representation() {
+    let (parts, _) = make_parts();
+    let s = ron::ser::to_string(&parts).unwrap();
+    let restored: Parts = ron::de::from_str(&s).unwrap();
+    assert_eq!(s, ron::ser::to_string(&restored).unwrap());
+}
+
+#[test]
+fn removed_id_stays_dead() {
+    let (parts, removed_id) = make_parts();
+    let s = ron::ser::to_string(&parts).unwrap();
+    let mut restored: Parts = ron::de::from_str(&s).unwrap();
+    assert!(!restored.is_alive(removed_id));
+    let new_id = restored.alloc_id();
+    assert_eq!(
+        new_id,
+        Id {
+            index: removed_id.index,
+            generation: 1,
+        }
+    );
+    assert!(!restored.is_alive(removed_id));
+}
+
+#[test]
+fn second_storage_in_the_same_module() {
+    let mut names = Names::new();
+    let id = names.alloc_id();
+    names.name.insert(id, Name("imp".into()));
+    let s = ron::ser::to_string(&names).unwrap();
+    let restored: Names = ron::de::from_str(&s).unwrap();
+    assert_eq!(restored.name.get(id), &Name("imp".into()));
+}
+
+#[test]
+fn change_tracking_is_restored() {
+    let (mut parts, _) = make_parts();
+    parts.enable_change_tracking();
+    let s = ron::ser::to_string(&parts).unwrap();
+    let mut restored: Parts = ron::de::from_str(&s).unwrap();
+    assert!(restored.is_change_tracking_enabled());
+    assert!(restored.pos.changes().unwrap().is_empty());
+    let id = restored.ids().next().unwrap();
+    restored.pos.get_mut(id).0 += 1;
+    assert_eq!(restored.pos.changes().unwrap().modified(), &[id]);
+}
+
+#[test]
+fn change_tracking_stays_disabled() {
+    let (parts, _) = make_parts();
+    let s = ron::ser::to_string(&parts).unwrap();
+    let restored: Parts = ron::de::from_str(&s).unwrap();
+    assert!(!restored.is_change_tracking_enabled());
+}
+
+#[test]
+fn no_change_tracking_field() {
+    let restored: Parts = ron::de::from_str("(ids: ([0], [true], []), pos: [])").unwrap();
+    assert!(!restored.is_change_tracking_enabled());
+}
+
+fn load_ids(s: &str) -> Result<rancor::IdAllocator, ron::de::Error> {
+    ron::de::from_str(s)
+}
+
+#[test]
+fn valid_free_list() {
+    let mut ids = load_ids("([0, 1, 3], [true, false, false], [2, 1])").unwrap();
+    let id: Id = ids.alloc();
+    assert_eq!(id, Id { index: 1, generation: 1 });
+    let id: Id = ids.alloc();
+    assert_eq!(id, Id { index: 2, generation: 3 });
+}
+
+#[test]
+fn duplicated_free_index() {
+    assert!(load_ids("([0, 1], [true, false], [1, 1])").is_err());
+}
+
+#[test]
+fn free_index_out_of_range() {
+    assert!(load_ids("([0, 1], [true, false], [2])").is_err());
+}
+
+#[test]
+fn alive_free_index() {
+    assert!(load_ids("([0, 1], [true, false], [0])").is_err());
+}
+
+#[test]
+fn different_lengths() {
+    assert!(load_ids("([0, 1], [true], [])").is_err());
+}
@@ -1,5 +1,6 @@
+use rancor::Join;
 use core::command::{self, Command};
-use core::{self, belongs_to, check, ObjId, PlayerId, State};
+use core::{self, check, ObjId, PlayerId, State};
 use core::movement::{self, Path, Pathfinder};
 use core::map;
 
@@ -10,10 +11,10 @@ pub struct Ai {
 }
 
 impl Ai {
-    pub fn new(id: PlayerId, map_radius: map::Distance) -> Self {
+    pub fn new(id: PlayerId, map_shape: &map::Shape) -> Self {
         Self {
             id,
-            pathfinder: Pathfinder::new(map_radius),
+            pathfinder: Pathfinder::new(map_shape),
         }
     }
 
@@ -21,11 +22,15 @@ impl Ai {
         self.pathfinder.fill_map(state, unit_id);
         let mut best_path = None;
         let mut best_cost = movement::max_cost();
-        let ids = state.parts().agent.ids();
-        for target_id in ids.filter(|&id| !belongs_to(state, self.id, id)) {
-            let target_pos = state.parts().pos.get(target_id).0;
-            for dir in map::dirs() {
-                let pos = map::Dir::get_neighbor_pos(target_pos, dir);
+        let parts = state.parts();
+        let attack_distance = parts.agent.get(unit_id).attack_distance;
+        let targets = (&parts.agent, &parts.pos, &parts.belongs_to).join();
+        for (_, _, target_pos, belongs_to) in targets {
+            if belongs_to.0 == self.id {
+                continue;
+            }
+            // Ranged units don't have to come close
+            for pos in map::range(target_pos.0, attack_distance) {
                 if !state.map().is_inboard(pos) {
                     continue;
                 }
@@ -44,8 +49,7 @@ impl Ai {
     }
 
     fn try_to_attack(&self, state: &State, unit_id: ObjId) -> Option<Command> {
-        let ids = state.parts().agent.ids();
-        for target_id in ids.filter(|&id| !belongs_to(state, self.id, id)) {
+        for target_id in core::enemy_agent_ids(state, self.id) {
             let command = command::Command::Attack(command::Attack {
                 attacker_id: unit_id,
                 target_id: target_id,
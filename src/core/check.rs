@@ -1,7 +1,9 @@
 use core::State;
 use core::command::{self, Command};
-use core::map::{self, PosHex};
-use core::{self, Attacks, Jokers, Moves};
+use core::component::Agent;
+use core::map::{self, Distance, PosHex};
+use core::movement;
+use core::{self, Attacks, Height, Jokers, Moves};
 
 pub fn check(state: &State, command: &Command) -> Result<(), Error> {
     match *command {
@@ -23,6 +25,9 @@ pub enum Error {
     NotEnoughMoves,
     NotEnoughAttacks,
     BadPos,
+    HeightDifferenceIsTooBig,
+    UnknownPrototype,
+    BadPrototype,
 }
 
 fn check_move_to(state: &State, command: &command::MoveTo) -> Result<(), Error> {
@@ -46,6 +51,9 @@ fn check_move_to(state: &State, command: &command::MoveTo) -> Result<(), Error>
         if core::is_tile_blocked(state, step.to) {
             return Err(Error::TileIsBlocked);
         }
+        if !movement::is_passable(state, step.from, step.to) {
+            return Err(Error::HeightDifferenceIsTooBig);
+        }
     }
     let cost = command.path.cost_for(state, command.id);
     if cost > agent.move_points {
@@ -61,6 +69,14 @@ fn check_create(state: &State, command: &command::Create) -> Result<(), Error> {
     if core::is_tile_blocked(state, command.pos) {
         return Err(Error::TileIsBlocked);
     }
+    if !state.prototypes.0.contains_key(&command.prototype) {
+        return Err(Error::UnknownPrototype);
+    }
+    let prototypes = &state.prototypes;
+    if let Err(err) = prototypes.components_with_overrides(&command.prototype, &command.overrides) {
+        error!("{}", err);
+        return Err(Error::BadPrototype);
+    }
     Ok(())
 }
 
@@ -83,7 +99,7 @@ pub fn check_attack_at(state: &State, command: &command::Attack, at: PosHex) ->
     if attacker_player_id != state.player_id() {
         return Err(Error::CanNotCommandEnemyUnits);
     }
-    if parts.agent.get_opt(command.target_id).is_none() {
+    if parts.strength.get_opt(command.target_id).is_none() {
         return Err(Error::BadTargetId);
     };
     if !state.map().is_inboard(at) {
@@ -93,12 +109,23 @@ pub fn check_attack_at(state: &State, command: &command::Attack, at: PosHex) ->
         return Err(Error::NotEnoughAttacks);
     }
     let dist = map::distance_hex(attacker_pos, at);
-    if dist > attacker_agent.attack_distance {
+    if dist > attack_distance(state, attacker_agent, attacker_pos, at) {
         return Err(Error::DistanceIsTooBig);
     }
     Ok(())
 }
 
+/// Ranged attackers on the higher ground can reach one tile further.
+fn attack_distance(state: &State, agent: &Agent, attacker_pos: PosHex, at: PosHex) -> Distance {
+    let distance = agent.attack_distance;
+    let is_ranged = distance > Distance(1);
+    if is_ranged && core::height_diff(state, attacker_pos, at) > Height(0) {
+        Distance(distance.0 + 1)
+    } else {
+        distance
+    }
+}
+
 fn check_end_turn(_: &State, _: &command::EndTurn) -> Result<(), Error> {
     Ok(())
 }
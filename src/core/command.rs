@@ -1,4 +1,5 @@
 use core::{ObjId, PlayerId};
+use core::component::ComponentOverride;
 use core::map::PosHex;
 use core::movement::Path;
 
@@ -10,11 +11,15 @@ pub enum Command {
     EndTurn(EndTurn),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Create {
     pub owner: Option<PlayerId>,
     pub pos: PosHex,
     pub prototype: String,
+
+    /// Applied on top of the prototype's components
+    #[serde(default)]
+    pub overrides: Vec<ComponentOverride>,
 }
 
 #[derive(Debug, Clone)]
@@ -1,15 +1,50 @@
 use core::{self, map, Attacks, Jokers, MovePoints, Moves, PlayerId};
 
+/// Generated by `rancor_storage!` together with `Parts`
+pub use core::Component;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Pos(pub map::PosHex);
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Blocker;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Strength {
-    pub base_strength: core::Strength,
-    pub strength: core::Strength,
+/// Defines a component together with its override: a struct with
+/// the same fields wrapped into `Option`s (`None` means "keep the value").
+macro_rules! overridable_component {
+    (
+        pub struct $name:ident / $override_name:ident {
+            $(pub $field:ident: $t:ty,)*
+        }
+    ) => {
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        pub struct $name {
+            $(pub $field: $t,)*
+        }
+
+        #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+        #[serde(default)]
+        pub struct $override_name {
+            $(pub $field: Option<$t>,)*
+        }
+
+        impl $name {
+            pub fn apply_override(&mut self, o: &$override_name) {
+                $(
+                    if let Some(ref value) = o.$field {
+                        self.$field = value.clone();
+                    }
+                )*
+            }
+        }
+    }
+}
+
+overridable_component! {
+    pub struct Strength / StrengthOverride {
+        pub base_strength: core::Strength,
+        pub strength: core::Strength,
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -20,28 +55,57 @@ pub struct Meta {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BelongsTo(pub PlayerId);
 
+overridable_component! {
+    pub struct Agent / AgentOverride {
+        // dynamic
+        pub moves: Moves,
+        pub attacks: Attacks,
+        pub jokers: Jokers,
+
+        // static
+        pub attack_distance: map::Distance,
+        pub move_points: MovePoints,
+        pub reactive_attacks: Attacks,
+        pub base_moves: Moves,
+        pub base_attacks: Attacks,
+        pub base_jokers: Jokers,
+    }
+}
+
+/// Prototype of an object that replaces this one when it's destroyed
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Agent {
-    // dynamic
-    pub moves: Moves,
-    pub attacks: Attacks,
-    pub jokers: Jokers,
-
-    // static
-    pub attack_distance: map::Distance,
-    pub move_points: MovePoints,
-    pub reactive_attacks: Attacks,
-    pub base_moves: Moves,
-    pub base_attacks: Attacks,
-    pub base_jokers: Jokers,
+pub struct Debris {
+    pub prototype: String,
 }
 
+/// Changes some fields of an inherited component (see `core::prototype`).
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub enum Component {
-    Pos(Pos),
-    Strength(Strength),
-    Meta(Meta),
-    BelongsTo(BelongsTo),
-    Agent(Agent),
-    Blocker(Blocker),
+pub enum ComponentOverride {
+    Strength(StrengthOverride),
+    Agent(AgentOverride),
+}
+
+impl ComponentOverride {
+    /// Name of the container that stores the overridden component
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ComponentOverride::Strength(_) => "strength",
+            ComponentOverride::Agent(_) => "agent",
+        }
+    }
+
+    /// Returns `false` if the component has another type.
+    pub fn apply(&self, component: &mut Component) -> bool {
+        match (self, component) {
+            (&ComponentOverride::Strength(ref o), &mut Component::Strength(ref mut c)) => {
+                c.apply_override(o);
+                true
+            }
+            (&ComponentOverride::Agent(ref o), &mut Component::Agent(ref mut c)) => {
+                c.apply_override(o);
+                true
+            }
+            _ => false,
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use core::{ObjId, State, Strength};
+use core::{self, ObjId, State, Strength};
 
 // TODO: subturns? EffectTime?
 //
@@ -35,6 +35,9 @@ pub fn apply(state: &mut State, id: ObjId, effect: &Effect) {
 }
 
 pub fn apply_kill(state: &mut State, id: ObjId) {
+    if let Some(pos) = state.parts.pos.get_opt(id).map(|pos| pos.0) {
+        core::remove_from_spatial_index(state, id, pos);
+    }
     state.parts.remove(id);
 }
 
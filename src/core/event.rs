@@ -1,5 +1,6 @@
 use std::collections::HashMap;
-use core::{Attacks, Jokers, Moves, ObjId, PlayerId, PosHex, State};
+use rancor::Join;
+use core::{self, Attacks, Jokers, Moves, ObjId, PlayerId, PosHex, State};
 use core::component::Component;
 use core::effect::{self, Effect};
 use core::movement::Path;
@@ -7,7 +8,6 @@ use core::movement::Path;
 #[derive(Clone, Debug)]
 pub struct Event {
     pub active_event: ActiveEvent,
-    pub actor_ids: Vec<ObjId>,
     pub effects: HashMap<ObjId, Vec<Effect>>,
 }
 
@@ -66,6 +66,7 @@ pub fn apply(state: &mut State, event: &Event) {
         }
     }
     apply_event(state, event);
+    debug_assert!(core::is_spatial_index_consistent(state));
 }
 
 pub fn apply_event(state: &mut State, event: &Event) {
@@ -81,21 +82,17 @@ pub fn apply_event(state: &mut State, event: &Event) {
 fn apply_event_create(state: &mut State, event: &Create) {
     let id = event.id;
     for component in &event.components {
-        match component.clone() {
-            Component::Pos(c) => state.parts.pos.insert(id, c),
-            Component::Strength(c) => state.parts.strength.insert(id, c),
-            Component::Meta(c) => state.parts.meta.insert(id, c),
-            Component::BelongsTo(c) => state.parts.belongs_to.insert(id, c),
-            Component::Agent(c) => state.parts.agent.insert(id, c),
-            Component::Blocker(c) => state.parts.blocker.insert(id, c),
-        }
+        core::insert_component(state, id, component.clone());
     }
 }
 
 fn apply_event_move_to(state: &mut State, event: &MoveTo) {
+    let from = state.parts.pos.get(event.id).0;
+    let to = *event.path.tiles().last().unwrap();
+    core::remove_from_spatial_index(state, event.id, from);
+    core::add_to_spatial_index(state, event.id, to);
+    state.parts.pos.get_mut(event.id).0 = to;
     let agent = state.parts.agent.get_mut(event.id);
-    let pos = state.parts.pos.get_mut(event.id);
-    pos.0 = *event.path.tiles().last().unwrap();
     if agent.moves.0 > 0 {
         agent.moves.0 -= event.cost.0;
     } else {
@@ -117,11 +114,9 @@ fn apply_event_attack(state: &mut State, event: &Attack) {
 }
 
 fn apply_event_end_turn(state: &mut State, event: &EndTurn) {
-    let ids: Vec<_> = state.parts.agent.ids().collect();
-    for id in ids {
-        let agent = state.parts.agent.get_mut(id);
-        let player_id = state.parts.belongs_to.get(id).0;
-        if player_id == event.player_id {
+    let parts = &mut state.parts;
+    for (_, mut agent, belongs_to) in (&mut parts.agent, &parts.belongs_to).join() {
+        if belongs_to.0 == event.player_id {
             agent.attacks.0 += agent.reactive_attacks.0;
         }
     }
@@ -129,14 +124,108 @@ fn apply_event_end_turn(state: &mut State, event: &EndTurn) {
 
 fn apply_event_begin_turn(state: &mut State, event: &BeginTurn) {
     state.player_id = event.player_id;
-    let ids: Vec<_> = state.parts.agent.ids().collect();
-    for id in ids {
-        let agent = state.parts.agent.get_mut(id);
-        let player_id = state.parts.belongs_to.get(id).0;
-        if player_id == event.player_id {
+    let parts = &mut state.parts;
+    for (_, mut agent, belongs_to) in (&mut parts.agent, &parts.belongs_to).join() {
+        if belongs_to.0 == event.player_id {
             agent.moves = agent.base_moves;
             agent.attacks = agent.base_attacks;
             agent.jokers = agent.base_jokers;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use core::component::{self, Component};
+    use core::effect::Effect;
+    use core::map::Distance;
+    use core::movement::{MovePoints, Path};
+    use core::{self, Attacks, Jokers, Moves, ObjId, PlayerId, PosHex, Prototypes, State};
+    use super::{apply, ActiveEvent, Create, EndTurn, Event, MoveTo};
+
+    fn agent() -> component::Agent {
+        component::Agent {
+            moves: Moves(1),
+            attacks: Attacks(1),
+            jokers: Jokers(1),
+            attack_distance: Distance(1),
+            move_points: MovePoints(3),
+            reactive_attacks: Attacks(1),
+            base_moves: Moves(1),
+            base_attacks: Attacks(1),
+            base_jokers: Jokers(1),
+        }
+    }
+
+    fn event(active_event: ActiveEvent, effects: HashMap<ObjId, Vec<Effect>>) -> Event {
+        Event {
+            active_event,
+            effects,
+        }
+    }
+
+    fn create(state: &mut State, pos: PosHex) -> ObjId {
+        let id = state.parts.alloc_id();
+        let components = vec![
+            Component::Pos(component::Pos(pos)),
+            Component::Agent(agent()),
+            Component::Blocker(component::Blocker),
+        ];
+        let active_event = ActiveEvent::Create(Create {
+            id,
+            pos,
+            prototype: "imp".into(),
+            components,
+        });
+        apply(state, &event(active_event, HashMap::new()));
+        id
+    }
+
+    fn move_to(state: &mut State, id: ObjId, to: PosHex) {
+        let from = state.parts.pos.get(id).0;
+        let active_event = ActiveEvent::MoveTo(MoveTo {
+            path: Path::new(vec![from, to]),
+            cost: Moves(1),
+            id,
+        });
+        apply(state, &event(active_event, HashMap::new()));
+    }
+
+    fn kill(state: &mut State, id: ObjId) {
+        let mut effects = HashMap::new();
+        effects.insert(id, vec![Effect::Kill]);
+        let active_event = ActiveEvent::EndTurn(EndTurn {
+            player_id: PlayerId(0),
+        });
+        apply(state, &event(active_event, effects));
+    }
+
+    #[test]
+    fn spatial_index() {
+        let mut state = State::new(Prototypes::default());
+        let pos_a = PosHex { q: 0, r: 0 };
+        let pos_b = PosHex { q: 1, r: 0 };
+        let pos_c = PosHex { q: 1, r: 1 };
+        let id_1 = create(&mut state, pos_a);
+        let id_2 = create(&mut state, pos_b);
+        assert!(core::is_spatial_index_consistent(&state));
+        assert_eq!(core::object_ids_at(&state, pos_a), vec![id_1]);
+        assert!(core::is_tile_blocked(&state, pos_b));
+
+        move_to(&mut state, id_1, pos_c);
+        assert!(core::is_spatial_index_consistent(&state));
+        assert!(core::object_ids_at(&state, pos_a).is_empty());
+        assert_eq!(core::object_ids_at(&state, pos_c), vec![id_1]);
+
+        kill(&mut state, id_2);
+        assert!(core::is_spatial_index_consistent(&state));
+        assert!(core::object_ids_at(&state, pos_b).is_empty());
+        assert!(!core::is_tile_blocked(&state, pos_b));
+
+        // The freed slot is reused by a new object on the same tile
+        let id_3 = create(&mut state, pos_b);
+        assert!(core::is_spatial_index_consistent(&state));
+        assert_eq!(core::object_ids_at(&state, pos_b), vec![id_3]);
+    }
+}
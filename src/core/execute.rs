@@ -2,8 +2,9 @@ use std::collections::{HashMap, VecDeque};
 use std::iter::FromIterator;
 use rand::{thread_rng, Rng};
 use core::map::PosHex;
-use core::{self, Moves, ObjId, PlayerId, State, TileType};
+use core::{self, Moves, ObjId, PlayerId, State};
 use core::command;
+use core::mapgen;
 use core::component::{self, Component};
 use core::command::Command;
 use core::event::{self, ActiveEvent, Event};
@@ -18,6 +19,9 @@ pub enum Phase {
 }
 
 /// A callback for visualization of the events/effects with the correct state.
+///
+/// During the `Post` phase the state's change tracking
+/// contains only the changes made by the event.
 type Cb<'c> = &'c mut FnMut(&State, &Event, Phase);
 
 pub fn execute(state: &mut State, command: &Command, cb: Cb) -> Result<(), Error> {
@@ -37,6 +41,7 @@ pub fn execute(state: &mut State, command: &Command, cb: Cb) -> Result<(), Error
 
 fn do_event(state: &mut State, cb: Cb, event: &Event) {
     cb(state, event, Phase::Pre);
+    state.parts.clear_changes();
     event::apply(state, event);
     cb(state, event, Phase::Post);
 }
@@ -71,7 +76,6 @@ fn do_move(state: &mut State, cb: Cb, id: ObjId, cost: Option<Moves>, path: Path
     let active_event = ActiveEvent::MoveTo(event::MoveTo { id, path, cost });
     let event = Event {
         active_event,
-        actor_ids: vec![id],
         effects: HashMap::new(),
     };
     do_event(state, cb, &event);
@@ -96,7 +100,10 @@ fn check_reaction_attacks_at(state: &mut State, target_id: ObjId, pos: PosHex) -
 }
 
 fn execute_create(state: &mut State, cb: Cb, command: &command::Create) {
-    let mut components = state.prototypes.0[&command.prototype].clone();
+    let mut components = state
+        .prototypes
+        .components_with_overrides(&command.prototype, &command.overrides)
+        .expect("The prototype was checked in `check_create`");
     if let Some(player_id) = command.owner {
         components.push(Component::BelongsTo(component::BelongsTo(player_id)));
     }
@@ -114,7 +121,6 @@ fn execute_create(state: &mut State, cb: Cb, command: &command::Create) {
     });
     let event = Event {
         active_event,
-        actor_ids: vec![id],
         effects: HashMap::new(),
     };
     do_event(state, cb, &event);
@@ -126,6 +132,14 @@ enum AttackStatus {
     Miss,
 }
 
+/// Chance to hit out of 6: attacks from the higher ground are more accurate.
+fn hit_chance(state: &State, command: &command::Attack) -> i32 {
+    let attacker_pos = state.parts.pos.get(command.attacker_id).0;
+    let target_pos = state.parts.pos.get(command.target_id).0;
+    let height_diff = core::height_diff(state, attacker_pos, target_pos);
+    3 + height_diff.0.signum()
+}
+
 fn execute_attack_internal(
     state: &mut State,
     cb: Cb,
@@ -138,7 +152,7 @@ fn execute_attack_internal(
         mode,
     });
     let mut effects = HashMap::new();
-    let effect = if thread_rng().gen_range(0, 6) < 3 {
+    let effect = if thread_rng().gen_range(0, 6) < hit_chance(state, command) {
         let strength = state.parts.strength.get(command.target_id);
         if strength.strength.0 > 1 {
             Effect::Wound(effect::Wound(core::Strength(1)))
@@ -152,13 +166,31 @@ fn execute_attack_internal(
         Effect::Kill | Effect::Wound(_) => AttackStatus::Hit,
         Effect::Miss => AttackStatus::Miss,
     };
+    let is_killed = match effect {
+        Effect::Kill => true,
+        _ => false,
+    };
+    let target_pos = state.parts.pos.get(command.target_id).0;
+    let debris = state.parts.debris.get_opt(command.target_id).cloned();
     effects.insert(command.target_id, vec![effect.clone()]);
     let event = Event {
         active_event,
-        actor_ids: vec![command.attacker_id],
         effects,
     };
     do_event(state, cb, &event);
+    if is_killed {
+        if let Some(debris) = debris {
+            let command_create = Command::Create(command::Create {
+                owner: None,
+                pos: target_pos,
+                prototype: debris.prototype,
+                overrides: Vec::new(),
+            });
+            if execute(state, &command_create, cb).is_err() {
+                error!("Can't create debris at {:?}", target_pos);
+            }
+        }
+    }
     status
 }
 
@@ -200,11 +232,9 @@ fn execute_end_turn(state: &mut State, cb: Cb, _: &command::EndTurn) {
         let active_event = ActiveEvent::EndTurn(event::EndTurn {
             player_id: player_id_old,
         });
-        let actor_ids = core::players_agent_ids(state, player_id_old);
         let effects = HashMap::new();
         let event = Event {
             active_event,
-            actor_ids,
             effects,
         };
         do_event(state, cb, &event);
@@ -214,11 +244,9 @@ fn execute_end_turn(state: &mut State, cb: Cb, _: &command::EndTurn) {
         let active_event = ActiveEvent::BeginTurn(event::BeginTurn {
             player_id: player_id_new,
         });
-        let actor_ids = core::players_agent_ids(state, player_id_new);
         let effects = HashMap::new();
         let event = Event {
             active_event,
-            actor_ids,
             effects,
         };
         do_event(state, cb, &event);
@@ -234,76 +262,105 @@ fn next_player_id(state: &State) -> PlayerId {
     }
 }
 
-fn random_free_pos(state: &State) -> Option<PosHex> {
-    let attempts = 30;
-    let radius = state.map().radius();
-    for _ in 0..attempts {
-        let pos = PosHex {
-            q: thread_rng().gen_range(-radius.0, radius.0),
-            r: thread_rng().gen_range(-radius.0, radius.0),
-        };
-        if state.map().is_inboard(pos) && !core::is_tile_blocked(state, pos) {
-            return Some(pos);
-        }
-    }
-    None
-}
-
+/// `None` if the player has no spawn sector or no free tile was found in it.
 fn random_free_sector_pos(state: &State, player_id: PlayerId) -> Option<PosHex> {
     let attempts = 30;
-    let radius = state.map().radius();
-    let start_sector_width = radius.0;
+    let min = state.map().shape().min();
+    let max = state.map().shape().max();
     for _ in 0..attempts {
-        let q = radius.0 - thread_rng().gen_range(0, start_sector_width);
         let pos = PosHex {
-            q: match player_id.0 {
-                0 => -q,
-                1 => q,
-                _ => unimplemented!(),
-            },
-            r: thread_rng().gen_range(-radius.0, radius.0),
+            q: thread_rng().gen_range(min.q, max.q + 1),
+            r: thread_rng().gen_range(min.r, max.r + 1),
         };
-        if state.map().is_inboard(pos) && !core::is_tile_blocked(state, pos) {
+        if state.map().is_inboard(pos) && mapgen::is_in_spawn_sector(pos, player_id) == Ok(true)
+            && !core::is_tile_blocked(state, pos)
+        {
             return Some(pos);
         }
     }
     None
 }
 
-pub fn create_terrain(state: &mut State) {
-    for _ in 0..15 {
-        let pos = match random_free_pos(state) {
-            Some(pos) => pos,
-            None => continue,
-        };
-        state.map.set_tile(pos, TileType::Rocks);
-    }
+/// Generates the terrain and returns positions of the obstacles.
+pub fn create_terrain(state: &mut State, options: &mapgen::Options) -> Vec<PosHex> {
+    let generated_map = mapgen::generate(state.map().shape(), options);
+    debug!("Generated map (seed = {}):\n{}", options.seed, generated_map);
+    state.map = generated_map.tiles;
+    state.heights = generated_map.heights;
+    core::rebuild_spatial_index(state);
+    generated_map.obstacles
 }
 
 // TODO: improve the API
-pub fn create_objects(state: &mut State, cb: Cb) {
+pub fn create_objects(state: &mut State, obstacles: &[PosHex], cb: Cb) {
     let player_id_initial = state.player_id;
+    for &pos in obstacles {
+        let command = Command::Create(command::Create {
+            prototype: "boulder".into(),
+            pos,
+            owner: None,
+            overrides: Vec::new(),
+        });
+        execute(state, &command, cb).expect("Can't create object");
+    }
     for &(owner, typename, count) in &[
-        (None, "boulder", 10),
-        (Some(PlayerId(0)), "swordsman", 2),
-        (Some(PlayerId(0)), "spearman", 2),
-        (Some(PlayerId(1)), "imp", 9),
+        (PlayerId(0), "swordsman", 2),
+        (PlayerId(0), "spearman", 2),
+        (PlayerId(1), "imp", 9),
     ] {
-        if let Some(player_id) = owner {
-            state.player_id = player_id;
-        }
+        state.player_id = owner;
         for _ in 0..count {
-            let pos = match owner {
-                Some(player_id) => random_free_sector_pos(state, player_id),
-                None => random_free_pos(state),
-            }.unwrap();
+            let pos = random_free_sector_pos(state, owner).unwrap();
             let command = Command::Create(command::Create {
                 prototype: typename.into(),
                 pos,
-                owner,
+                owner: Some(owner),
+                overrides: Vec::new(),
             });
             execute(state, &command, cb).expect("Can't create object");
         }
     }
     state.player_id = player_id_initial;
 }
+
+#[cfg(test)]
+mod tests {
+    use core::map::PosHex;
+    use core::{command, component, Height, Prototypes, State};
+    use super::hit_chance;
+
+    /// The attacker is at `(0, 0)`, the target is at `(1, 0)`.
+    fn attack_chance(attacker_height: Height, target_height: Height) -> i32 {
+        let mut state = State::new(Prototypes::default());
+        let attacker_pos = PosHex { q: 0, r: 0 };
+        let target_pos = PosHex { q: 1, r: 0 };
+        state.heights.set_tile(attacker_pos, attacker_height);
+        state.heights.set_tile(target_pos, target_height);
+        let attacker_id = state.parts.alloc_id();
+        let target_id = state.parts.alloc_id();
+        state.parts.pos.insert(attacker_id, component::Pos(attacker_pos));
+        state.parts.pos.insert(target_id, component::Pos(target_pos));
+        let command = command::Attack {
+            attacker_id,
+            target_id,
+        };
+        hit_chance(&state, &command)
+    }
+
+    #[test]
+    fn same_height() {
+        assert_eq!(attack_chance(Height(0), Height(0)), 3);
+    }
+
+    #[test]
+    fn from_higher_ground() {
+        assert_eq!(attack_chance(Height(1), Height(0)), 4);
+        assert_eq!(attack_chance(Height(2), Height(0)), 4);
+    }
+
+    #[test]
+    fn from_lower_ground() {
+        assert_eq!(attack_chance(Height(0), Height(1)), 2);
+        assert_eq!(attack_chance(Height(0), Height(2)), 2);
+    }
+}
@@ -1,5 +1,7 @@
+use std::collections::HashSet;
+use std::fmt::{self, Debug};
 use std::iter::repeat;
-use std::fmt::Debug;
+use std::ops::{Add, Sub};
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Distance(pub i32);
@@ -15,7 +17,7 @@ pub struct PosCube<T: Debug + Copy = i32> {
 
 /// Axial coordinates
 /// <http://www.redblobgames.com/grids/hexagons/#coordinates-axial>
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PosHex<T: Debug + Copy = i32> {
     /// column
     pub q: T,
@@ -24,6 +26,34 @@ pub struct PosHex<T: Debug + Copy = i32> {
     pub r: T,
 }
 
+impl<T: Debug + Copy + Add<Output = T>> Add for PosHex<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        PosHex {
+            q: self.q + other.q,
+            r: self.r + other.r,
+        }
+    }
+}
+
+impl<T: Debug + Copy + Sub<Output = T>> Sub for PosHex<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        PosHex {
+            q: self.q - other.q,
+            r: self.r - other.r,
+        }
+    }
+}
+
+impl<T: Debug + Copy + fmt::Display> fmt::Display for PosHex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.q, self.r)
+    }
+}
+
 // TODO: merge hex_to_cube_f and hex_to_cube
 pub fn hex_to_cube_f(hex: PosHex<f32>) -> PosCube<f32> {
     PosCube {
@@ -83,57 +113,329 @@ pub fn distance_hex(a: PosHex, b: PosHex) -> Distance {
     distance_cube(hex_to_cube(a), hex_to_cube(b))
 }
 
-fn is_inboard(radius: Distance, pos: PosHex) -> bool {
-    let origin = PosHex { q: 0, r: 0 };
-    distance_hex(origin, pos) <= radius
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
 }
 
-#[derive(Clone, Debug)]
-pub struct HexIter {
-    cursor: PosHex,
-    radius: Distance,
-}
-
-impl HexIter {
-    fn new(radius: Distance) -> Self {
-        let mut iter = Self {
-            cursor: PosHex {
-                q: -radius.0,
-                r: -radius.0,
-            },
-            radius,
+fn cube_lerp(a: PosCube<f32>, b: PosCube<f32>, t: f32) -> PosCube<f32> {
+    PosCube {
+        x: lerp(a.x, b.x, t),
+        y: lerp(a.y, b.y, t),
+        z: lerp(a.z, b.z, t),
+    }
+}
+
+/// <http://www.redblobgames.com/grids/hexagons/#line-drawing>
+pub fn line(from: PosHex, to: PosHex) -> Vec<PosHex> {
+    let n = distance_hex(from, to).0;
+    let to_cube_f = |pos: PosHex, nudge: f32| PosCube {
+        x: pos.q as f32 + nudge,
+        y: (-pos.q - pos.r) as f32 + nudge,
+        z: pos.r as f32 - nudge * 2.0,
+    };
+    // Nudge the endpoints a little so the points on the edges
+    // between two hexes are always rounded in the same direction.
+    let nudge = 1e-6;
+    let a = to_cube_f(from, nudge);
+    let b = to_cube_f(to, nudge);
+    let step = 1.0 / (n.max(1) as f32);
+    (0..n + 1)
+        .map(|i| cube_to_hex(cube_round(cube_lerp(a, b, step * i as f32))))
+        .collect()
+}
+
+/// Tiles that are exactly `radius` tiles away from the `center`
+///
+/// <http://www.redblobgames.com/grids/hexagons/#rings>
+pub fn ring(center: PosHex, radius: Distance) -> Vec<PosHex> {
+    if radius.0 == 0 {
+        return vec![center];
+    }
+    let mut tiles = Vec::new();
+    let start_dir = Dir::West;
+    let mut pos = center;
+    for _ in 0..radius.0 {
+        pos = Dir::get_neighbor_pos(pos, start_dir);
+    }
+    for dir in dirs() {
+        for _ in 0..radius.0 {
+            tiles.push(pos);
+            pos = Dir::get_neighbor_pos(pos, dir);
+        }
+    }
+    tiles
+}
+
+/// Rings from the `center` up to `radius`, ordered from the innermost one
+///
+/// <http://www.redblobgames.com/grids/hexagons/#rings-spiral>
+pub fn spiral(center: PosHex, radius: Distance) -> Vec<PosHex> {
+    let mut tiles = Vec::new();
+    for r in 0..radius.0 + 1 {
+        tiles.extend(ring(center, Distance(r)));
+    }
+    tiles
+}
+
+/// All tiles within `radius` tiles from the `center`
+pub fn range(center: PosHex, radius: Distance) -> Vec<PosHex> {
+    range_intersection(center, radius, center, radius)
+}
+
+/// Tiles that are within `radius_a` from `a` and within `radius_b` from `b`
+///
+/// <http://www.redblobgames.com/grids/hexagons/#range-intersection>
+pub fn range_intersection(
+    a: PosHex,
+    radius_a: Distance,
+    b: PosHex,
+    radius_b: Distance,
+) -> Vec<PosHex> {
+    let a = hex_to_cube(a);
+    let b = hex_to_cube(b);
+    let (ra, rb) = (radius_a.0, radius_b.0);
+    let x_min = (a.x - ra).max(b.x - rb);
+    let x_max = (a.x + ra).min(b.x + rb);
+    let y_min = (a.y - ra).max(b.y - rb);
+    let y_max = (a.y + ra).min(b.y + rb);
+    let z_min = (a.z - ra).max(b.z - rb);
+    let z_max = (a.z + ra).min(b.z + rb);
+    let mut tiles = Vec::new();
+    for x in x_min..x_max + 1 {
+        for y in y_min.max(-x - z_max)..y_max.min(-x - z_min) + 1 {
+            let z = -x - y;
+            tiles.push(cube_to_hex(PosCube { x, y, z }));
+        }
+    }
+    tiles
+}
+
+/// Rotates `pos` around the `center` by `steps` times 60 degrees.
+/// One positive step turns the `SouthEast` neighbor into the `SouthWest` one,
+/// negative steps rotate in the opposite direction.
+///
+/// <http://www.redblobgames.com/grids/hexagons/#rotation>
+pub fn rotate(pos: PosHex, center: PosHex, steps: i32) -> PosHex {
+    let mut cube = hex_to_cube(pos - center);
+    for _ in 0..steps.rem_euclid(6) {
+        cube = PosCube {
+            x: -cube.z,
+            y: -cube.x,
+            z: -cube.y,
         };
-        iter.inc_cursor_with_hex_bounds();
-        iter
     }
+    cube_to_hex(cube) + center
+}
+
+/// Cube coordinate axis that stays the same during a reflection
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Axis {
+    Q,
+    R,
+    S,
+}
 
-    fn inc_cursor(&mut self) {
-        self.cursor.q += 1;
-        if self.cursor.q > self.radius.0 {
-            self.cursor.q = -self.radius.0;
-            self.cursor.r += 1;
+/// Reflects `pos` across the `axis` going through the `center`.
+///
+/// `Axis::R` keeps the rows, so it's a reflection across a vertical line on the screen.
+///
+/// <http://www.redblobgames.com/grids/hexagons/#reflection>
+pub fn reflect(pos: PosHex, center: PosHex, axis: Axis) -> PosHex {
+    let c = hex_to_cube(pos - center);
+    let reflected = match axis {
+        Axis::Q => PosCube {
+            x: c.x,
+            y: c.z,
+            z: c.y,
+        },
+        Axis::R => PosCube {
+            x: c.y,
+            y: c.x,
+            z: c.z,
+        },
+        Axis::S => PosCube {
+            x: c.z,
+            y: c.y,
+            z: c.x,
+        },
+    };
+    cube_to_hex(reflected) + center
+}
+
+/// Tiles that can be reached from `start` in at most `max_steps` steps
+/// without visiting the blocked tiles
+///
+/// <http://www.redblobgames.com/grids/hexagons/#range-obstacles>
+pub fn reachable<F>(start: PosHex, max_steps: Distance, is_blocked: F) -> Vec<PosHex>
+where
+    F: Fn(PosHex) -> bool,
+{
+    let mut visited = HashSet::new();
+    visited.insert(start);
+    let mut tiles = vec![start];
+    let mut fringe = vec![start];
+    for _ in 0..max_steps.0 {
+        let mut next_fringe = Vec::new();
+        for pos in fringe {
+            for dir in dirs() {
+                let neighbor = Dir::get_neighbor_pos(pos, dir);
+                if is_blocked(neighbor) || !visited.insert(neighbor) {
+                    continue;
+                }
+                tiles.push(neighbor);
+                next_fringe.push(neighbor);
+            }
         }
+        fringe = next_fringe;
     }
+    tiles
+}
 
-    fn inc_cursor_with_hex_bounds(&mut self) {
-        self.inc_cursor();
-        while !is_inboard(self.radius, self.cursor) && self.cursor.r < self.radius.0 + 1 {
-            self.inc_cursor();
+/// Map shape: axial bounds plus a membership mask.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Shape {
+    min: PosHex,
+    max: PosHex,
+    width: i32,
+    mask: Vec<bool>,
+}
+
+impl Shape {
+    /// Creates a shape with tiles from the `min..=max` bounds
+    /// for which `is_inboard` returns `true`.
+    pub fn from_fn<F: Fn(PosHex) -> bool>(min: PosHex, max: PosHex, is_inboard: F) -> Self {
+        assert!(min.q <= max.q && min.r <= max.r);
+        let width = max.q - min.q + 1;
+        let mut mask = Vec::new();
+        for r in min.r..max.r + 1 {
+            for q in min.q..max.q + 1 {
+                mask.push(is_inboard(PosHex { q, r }));
+            }
+        }
+        Self {
+            min,
+            max,
+            width,
+            mask,
         }
     }
+
+    /// A hexagon of the given radius with the center at `[0, 0]`
+    pub fn hexagon(radius: Distance) -> Self {
+        let origin = PosHex { q: 0, r: 0 };
+        let min = PosHex {
+            q: -radius.0,
+            r: -radius.0,
+        };
+        let max = PosHex {
+            q: radius.0,
+            r: radius.0,
+        };
+        Self::from_fn(min, max, |pos| distance_hex(origin, pos) <= radius)
+    }
+
+    /// A parallelogram (a rectangle in axial coordinates) centered around `[0, 0]`
+    pub fn parallelogram(width: i32, height: i32) -> Self {
+        let min = PosHex {
+            q: -width / 2,
+            r: -height / 2,
+        };
+        let max = PosHex {
+            q: min.q + width - 1,
+            r: min.r + height - 1,
+        };
+        Self::from_fn(min, max, |_| true)
+    }
+
+    /// A rectangle in "odd-r" offset coordinates centered around `[0, 0]`
+    ///
+    /// <http://www.redblobgames.com/grids/hexagons/#map-storage>
+    pub fn rectangle(width: i32, height: i32) -> Self {
+        let min_col = -width / 2;
+        let max_col = min_col + width - 1;
+        let min_r = -height / 2;
+        let max_r = min_r + height - 1;
+        let row_shift = |r: i32| (r - (r & 1)) / 2;
+        let min = PosHex {
+            q: min_col - row_shift(max_r),
+            r: min_r,
+        };
+        let max = PosHex {
+            q: max_col - row_shift(min_r),
+            r: max_r,
+        };
+        Self::from_fn(min, max, |pos| {
+            let col = pos.q + row_shift(pos.r);
+            col >= min_col && col <= max_col
+        })
+    }
+
+    /// Creates a new shape that contains only those tiles
+    /// of this shape for which `is_inboard` returns `true`.
+    ///
+    /// Useful for islands, corridors and other custom masks.
+    pub fn filter<F: Fn(PosHex) -> bool>(&self, is_inboard: F) -> Self {
+        Self::from_fn(self.min, self.max, |pos| {
+            self.is_inboard(pos) && is_inboard(pos)
+        })
+    }
+
+    /// Bounds' corner with the minimal coordinates
+    pub fn min(&self) -> PosHex {
+        self.min
+    }
+
+    /// Bounds' corner with the maximal coordinates
+    pub fn max(&self) -> PosHex {
+        self.max
+    }
+
+    fn is_in_bounds(&self, pos: PosHex) -> bool {
+        pos.q >= self.min.q && pos.q <= self.max.q && pos.r >= self.min.r && pos.r <= self.max.r
+    }
+
+    fn index(&self, pos: PosHex) -> usize {
+        let i = (pos.q - self.min.q) + (pos.r - self.min.r) * self.width;
+        i as usize
+    }
+
+    pub fn is_inboard(&self, pos: PosHex) -> bool {
+        self.is_in_bounds(pos) && self.mask[self.index(pos)]
+    }
+
+    pub fn iter(&self) -> HexIter {
+        HexIter {
+            shape: self,
+            index: 0,
+        }
+    }
+
+    pub fn tiles_count(&self) -> usize {
+        self.mask.iter().filter(|&&is_inboard| is_inboard).count()
+    }
 }
 
-impl Iterator for HexIter {
+#[derive(Clone, Debug)]
+pub struct HexIter<'a> {
+    shape: &'a Shape,
+    index: usize,
+}
+
+impl<'a> Iterator for HexIter<'a> {
     type Item = PosHex;
 
     fn next(&mut self) -> Option<PosHex> {
-        if self.cursor.r >= self.radius.0 + 1 {
-            None
-        } else {
-            let current = self.cursor;
-            self.inc_cursor_with_hex_bounds();
-            Some(current)
+        while self.index < self.shape.mask.len() {
+            let index = self.index as i32;
+            self.index += 1;
+            if self.shape.mask[index as usize] {
+                return Some(PosHex {
+                    q: self.shape.min.q + index % self.shape.width,
+                    r: self.shape.min.r + index / self.shape.width,
+                });
+            }
         }
+        None
     }
 }
 
@@ -142,51 +444,57 @@ impl Iterator for HexIter {
 /// [-1, 1]  [0, 0]  [1, -1]
 ///     [ 0, 1]  [ 1, 0]
 ///
-#[derive(Debug, Clone)]
-pub struct HexMap<T: Copy + Debug> {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HexMap<T: Clone + Debug> {
     tiles: Vec<T>,
-    size: Distance,
-    radius: Distance,
+    shape: Shape,
 }
 
-impl<T: Copy + Default + Debug> HexMap<T> {
-    pub fn new(radius: Distance) -> Self {
-        let size = Distance(radius.0 * 2 + 1);
-        let tiles_count = (size.0 * size.0) as usize;
+impl<T: Clone + Default + Debug> HexMap<T> {
+    pub fn new(shape: Shape) -> Self {
+        let tiles_count = shape.mask.len();
         let tiles = repeat(Default::default()).take(tiles_count).collect();
-        Self {
-            tiles,
-            size,
-            radius,
-        }
+        Self { tiles, shape }
     }
 
-    pub fn radius(&self) -> Distance {
-        self.radius
+    pub fn shape(&self) -> &Shape {
+        &self.shape
     }
 
     pub fn iter(&self) -> HexIter {
-        HexIter::new(self.radius)
+        self.shape.iter()
     }
 
     pub fn is_inboard(&self, pos: PosHex) -> bool {
-        is_inboard(self.radius, pos)
+        self.shape.is_inboard(pos)
     }
 
-    fn hex_to_index(&self, hex: PosHex) -> usize {
-        let i = (hex.r + self.radius.0) + (hex.q + self.radius.0) * self.size.0;
-        i as usize
+    pub fn tile_ref(&self, pos: PosHex) -> &T {
+        assert!(self.is_inboard(pos));
+        &self.tiles[self.shape.index(pos)]
     }
 
-    pub fn tile(&self, pos: PosHex) -> T {
+    pub fn tile_mut(&mut self, pos: PosHex) -> &mut T {
         assert!(self.is_inboard(pos));
-        self.tiles[self.hex_to_index(pos)]
+        let index = self.shape.index(pos);
+        &mut self.tiles[index]
     }
 
     pub fn set_tile(&mut self, pos: PosHex, tile: T) {
-        assert!(self.is_inboard(pos));
-        let index = self.hex_to_index(pos);
-        self.tiles[index] = tile;
+        *self.tile_mut(pos) = tile;
+    }
+
+    /// Sets every tile to the value
+    pub fn fill(&mut self, tile: T) {
+        for t in &mut self.tiles {
+            *t = tile.clone();
+        }
+    }
+}
+
+impl<T: Copy + Default + Debug> HexMap<T> {
+    pub fn tile(&self, pos: PosHex) -> T {
+        *self.tile_ref(pos)
     }
 }
 
@@ -228,30 +536,27 @@ impl Dir {
         }
     }
 
-    // TODO:
-    /*
-    fn hex_direction(direction: Dir) {
-        return axial_directions[direction]
+    pub fn hex_direction(dir: Dir) -> PosHex {
+        let diff = DIR_TO_POS_DIFF[dir.to_int() as usize];
+        PosHex {
+            q: diff[0],
+            r: diff[1],
+        }
     }
-    */
 
     pub fn get_dir_from_to(from: PosHex, to: PosHex) -> Dir {
         assert_eq!(distance_hex(from, to), Distance(1));
-        let diff = [to.q - from.q, to.r - from.r];
+        let diff = to - from;
         for dir in dirs() {
-            if diff == DIR_TO_POS_DIFF[dir.to_int() as usize] {
+            if diff == Dir::hex_direction(dir) {
                 return dir;
             }
         }
-        panic!("impossible positions: {:?}, {:?}", from, to); // TODO: implement Display for PosHex
+        panic!("impossible positions: {}, {}", from, to);
     }
 
     pub fn get_neighbor_pos(pos: PosHex, dir: Dir) -> PosHex {
-        let diff = DIR_TO_POS_DIFF[dir.to_int() as usize];
-        PosHex {
-            q: pos.q + diff[0],
-            r: pos.r + diff[1],
-        }
+        pos + Dir::hex_direction(dir)
     }
 }
 
@@ -278,3 +583,199 @@ impl Iterator for DirIter {
         next_dir
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::{distance_hex, line, range, range_intersection, reachable, ring, spiral, Dir,
+                Distance, HexMap, PosHex, Shape};
+
+    fn pos(q: i32, r: i32) -> PosHex {
+        PosHex { q, r }
+    }
+
+    #[test]
+    fn parallelogram() {
+        let shape = Shape::parallelogram(3, 2);
+        assert_eq!(shape.min(), pos(-1, -1));
+        assert_eq!(shape.max(), pos(1, 0));
+        assert_eq!(shape.tiles_count(), 6);
+        let tiles: Vec<_> = shape.iter().collect();
+        let expected = vec![pos(-1, -1), pos(0, -1), pos(1, -1), pos(-1, 0), pos(0, 0), pos(1, 0)];
+        assert_eq!(tiles, expected);
+        assert!(!shape.is_inboard(pos(-2, -1)));
+        assert!(!shape.is_inboard(pos(1, 1)));
+        assert!(!shape.is_inboard(pos(-1, -2)));
+    }
+
+    #[test]
+    fn rectangle() {
+        let shape = Shape::rectangle(4, 3);
+        assert_eq!(shape.min(), pos(-2, -1));
+        assert_eq!(shape.max(), pos(2, 1));
+        assert_eq!(shape.tiles_count(), 12);
+        // Odd rows are shifted: every row has 4 tiles
+        let tiles: Vec<_> = shape.iter().collect();
+        let expected = vec![
+            pos(-1, -1),
+            pos(0, -1),
+            pos(1, -1),
+            pos(2, -1),
+            pos(-2, 0),
+            pos(-1, 0),
+            pos(0, 0),
+            pos(1, 0),
+            pos(-2, 1),
+            pos(-1, 1),
+            pos(0, 1),
+            pos(1, 1),
+        ];
+        assert_eq!(tiles, expected);
+        // The bounds' corners outside of the rectangle
+        assert!(!shape.is_inboard(pos(-2, -1)));
+        assert!(!shape.is_inboard(pos(2, 0)));
+        assert!(!shape.is_inboard(pos(2, 1)));
+    }
+
+    #[test]
+    fn filter() {
+        let shape = Shape::hexagon(Distance(1)).filter(|pos| pos.r != 0);
+        assert_eq!(shape.min(), pos(-1, -1));
+        assert_eq!(shape.max(), pos(1, 1));
+        let tiles: Vec<_> = shape.iter().collect();
+        assert_eq!(tiles, vec![pos(0, -1), pos(1, -1), pos(-1, 1), pos(0, 1)]);
+        assert!(!shape.is_inboard(pos(0, 0)));
+        assert!(!shape.is_inboard(pos(-1, -1)));
+    }
+
+    #[test]
+    fn tiles_at_the_edges() {
+        let origin = pos(0, 0);
+        let shapes = vec![
+            Shape::parallelogram(5, 4),
+            Shape::rectangle(6, 5),
+            Shape::rectangle(7, 4).filter(|pos| distance_hex(origin, pos) <= Distance(3)),
+        ];
+        for shape in shapes {
+            let mut map = HexMap::new(shape.clone());
+            let tiles: Vec<_> = shape.iter().collect();
+            for (i, &pos) in tiles.iter().enumerate() {
+                map.set_tile(pos, i);
+            }
+            for (i, &pos) in tiles.iter().enumerate() {
+                assert_eq!(map.tile(pos), i);
+            }
+            assert!(tiles.iter().any(|pos| pos.q < 0 && pos.r < 0));
+            let (min, max) = (shape.min(), shape.max());
+            assert_eq!(shape.index(min), 0);
+            assert_eq!(shape.index(max), shape.mask.len() - 1);
+            for &(dq, dr) in &[(-1, 0), (0, -1)] {
+                assert!(!shape.is_inboard(pos(min.q + dq, min.r + dr)));
+            }
+            for &(dq, dr) in &[(1, 0), (0, 1)] {
+                assert!(!shape.is_inboard(pos(max.q + dq, max.r + dr)));
+            }
+        }
+    }
+
+    fn is_unique(tiles: &[PosHex]) -> bool {
+        let set: HashSet<_> = tiles.iter().collect();
+        set.len() == tiles.len()
+    }
+
+    #[test]
+    fn ring_sizes() {
+        let center = pos(2, -1);
+        assert_eq!(ring(center, Distance(0)), vec![center]);
+        for r in 1..6 {
+            let tiles = ring(center, Distance(r));
+            assert_eq!(tiles.len(), 6 * r as usize);
+            assert!(is_unique(&tiles));
+            assert!(tiles.iter().all(|&p| distance_hex(center, p) == Distance(r)));
+        }
+    }
+
+    #[test]
+    fn spiral_count() {
+        let center = pos(-1, 3);
+        for r in 0..6 {
+            let tiles = spiral(center, Distance(r));
+            assert_eq!(tiles.len(), (1 + 3 * r * (r + 1)) as usize);
+            assert!(is_unique(&tiles));
+            assert_eq!(tiles[0], center);
+            let distances: Vec<_> = tiles.iter().map(|&p| distance_hex(center, p)).collect();
+            assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn line_endpoints_and_length() {
+        let pairs = [
+            (pos(0, 0), pos(0, 0)),
+            (pos(0, 0), pos(3, 0)),
+            (pos(-2, 1), pos(3, -4)),
+            (pos(4, -1), pos(-3, 2)),
+            (pos(0, -3), pos(0, 3)),
+        ];
+        for &(from, to) in &pairs {
+            let tiles = line(from, to);
+            assert_eq!(tiles.len(), distance_hex(from, to).0 as usize + 1);
+            assert_eq!(tiles[0], from);
+            assert_eq!(*tiles.last().unwrap(), to);
+            for w in tiles.windows(2) {
+                assert_eq!(distance_hex(w[0], w[1]), Distance(1));
+            }
+        }
+    }
+
+    #[test]
+    fn range_contains_all_tiles_within_radius() {
+        let center = pos(1, 1);
+        for r in 0..5 {
+            let tiles = range(center, Distance(r));
+            assert_eq!(tiles.len(), (1 + 3 * r * (r + 1)) as usize);
+            assert!(is_unique(&tiles));
+            assert!(tiles.iter().all(|&p| distance_hex(center, p) <= Distance(r)));
+        }
+    }
+
+    #[test]
+    fn range_intersection_is_the_common_part() {
+        let (a, b) = (pos(0, 0), pos(3, -1));
+        let (ra, rb) = (Distance(2), Distance(3));
+        let tiles = range_intersection(a, ra, b, rb);
+        assert!(is_unique(&tiles));
+        let expected: HashSet<_> = range(a, ra)
+            .into_iter()
+            .filter(|&p| distance_hex(b, p) <= rb)
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(tiles.into_iter().collect::<HashSet<_>>(), expected);
+        let far = pos(10, 0);
+        assert!(range_intersection(a, ra, far, rb).is_empty());
+    }
+
+    #[test]
+    fn reachable_with_obstacles() {
+        let start = pos(0, 0);
+        let shape = Shape::hexagon(Distance(3));
+        let no_obstacles = reachable(start, Distance(2), |p| !shape.is_inboard(p));
+        assert_eq!(no_obstacles.len(), 19);
+        // A wall around the start with one gap in the east
+        let gap = Dir::get_neighbor_pos(start, Dir::East);
+        let is_blocked = |p: PosHex| {
+            !shape.is_inboard(p) || (distance_hex(start, p) == Distance(1) && p != gap)
+        };
+        let tiles = reachable(start, Distance(2), is_blocked);
+        assert!(is_unique(&tiles));
+        assert_eq!(tiles[0], start);
+        assert!(tiles.contains(&gap));
+        assert!(tiles.iter().all(|&p| !is_blocked(p)));
+        // Only the start, the gap and the gap's outer neighbors
+        assert_eq!(tiles.len(), 2 + 3);
+        let far = Dir::get_neighbor_pos(Dir::get_neighbor_pos(start, Dir::West), Dir::West);
+        assert!(!tiles.contains(&far));
+        // Out through the gap and around the wall
+        assert!(reachable(start, Distance(10), is_blocked).contains(&far));
+    }
+}
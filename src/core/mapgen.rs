@@ -0,0 +1,624 @@
+use std::collections::VecDeque;
+use std::fmt;
+use rand::{IsaacRng, Rng, SeedableRng};
+use core::map::{self, dirs, Axis, Dir, HexMap, PosHex, Shape};
+use core::{Height, PlayerId, TileType, MAX_CLIMB};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    None,
+
+    /// Rotation by 180 degrees around the center of the map
+    Point,
+
+    /// Reflection across the vertical line that separates the spawn sectors
+    Mirror,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Options {
+    pub seed: u32,
+
+    /// Part of the tiles covered with rocks (passable, but costly)
+    pub rocks_density: f32,
+
+    /// Part of the tiles covered with obstacles (boulders)
+    pub obstacle_density: f32,
+
+    /// Part of the tiles covered with hills
+    pub hills_density: f32,
+
+    pub max_height: Height,
+
+    /// Part of the tiles raised to `max_height` without smoothing:
+    /// such cliffs can't be climbed from the low ground.
+    pub cliffs_density: f32,
+
+    /// Max number of tiles in one rock formation, obstacle cluster or hill
+    pub cluster_size: i32,
+
+    /// Number of passages in the obstacle wall between the spawn sectors.
+    /// Zero means that there's no wall at all.
+    pub chokepoints: i32,
+
+    pub symmetry: Symmetry,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            rocks_density: 0.15,
+            obstacle_density: 0.1,
+            hills_density: 0.2,
+            max_height: Height(2),
+            cliffs_density: 0.05,
+            cluster_size: 4,
+            chokepoints: 0,
+            symmetry: Symmetry::Mirror,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GeneratedMap {
+    pub tiles: HexMap<TileType>,
+    pub heights: HexMap<Height>,
+    pub obstacles: Vec<PosHex>,
+}
+
+impl GeneratedMap {
+    pub fn is_obstacle(&self, pos: PosHex) -> bool {
+        self.obstacles.contains(&pos)
+    }
+}
+
+/// Prints the map as an ASCII hex grid:
+/// `.` - plain, `1`-`9` - plain with the height, `^` - rocks, `#` - obstacle.
+impl fmt::Display for GeneratedMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shape = self.tiles.shape();
+        let column = |pos: PosHex| pos.q * 2 + pos.r;
+        let min_column = shape.iter().map(&column).min().unwrap_or(0);
+        let max_column = shape.iter().map(&column).max().unwrap_or(0);
+        for r in shape.min().r..shape.max().r + 1 {
+            let mut line = vec![' '; (max_column - min_column + 1) as usize];
+            for q in shape.min().q..shape.max().q + 1 {
+                let pos = PosHex { q, r };
+                if !self.tiles.is_inboard(pos) {
+                    continue;
+                }
+                let c = if self.is_obstacle(pos) {
+                    '#'
+                } else {
+                    let height = self.heights.tile(pos).0;
+                    match self.tiles.tile(pos) {
+                        TileType::Plain if height > 0 && height < 10 => {
+                            (b'0' + height as u8) as char
+                        }
+                        TileType::Plain => '.',
+                        TileType::Rocks => '^',
+                    }
+                };
+                line[(column(pos) - min_column) as usize] = c;
+            }
+            let line: String = line.into_iter().collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Only players 0 and 1 have spawn sectors
+    NoSpawnSector(PlayerId),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NoSpawnSector(player_id) => {
+                write!(f, "Player {} has no spawn sector", player_id.0)
+            }
+        }
+    }
+}
+
+/// Spawn sectors are the left (player 0) and the right (player 1) parts of the map
+/// separated by a two tiles wide gap in the middle.
+pub fn is_in_spawn_sector(pos: PosHex, player_id: PlayerId) -> Result<bool, Error> {
+    let column = pos.q * 2 + pos.r;
+    match player_id.0 {
+        0 => Ok(column < -1),
+        1 => Ok(column > 1),
+        _ => Err(Error::NoSpawnSector(player_id)),
+    }
+}
+
+/// Returns the position itself and all its symmetric images.
+fn images(symmetry: Symmetry, pos: PosHex) -> Vec<PosHex> {
+    let origin = PosHex { q: 0, r: 0 };
+    let image = match symmetry {
+        Symmetry::None => return vec![pos],
+        Symmetry::Point => map::rotate(pos, origin, 3),
+        Symmetry::Mirror => map::reflect(pos, origin, Axis::R),
+    };
+    if image == pos {
+        vec![pos]
+    } else {
+        vec![pos, image]
+    }
+}
+
+struct Generator {
+    rng: IsaacRng,
+    options: Options,
+    tiles: HexMap<TileType>,
+    heights: HexMap<Height>,
+    cliffs: HexMap<bool>,
+    blocked: HexMap<bool>,
+}
+
+impl Generator {
+    fn new(shape: &Shape, options: &Options) -> Self {
+        Self {
+            rng: IsaacRng::from_seed(&[options.seed]),
+            options: options.clone(),
+            tiles: HexMap::new(shape.clone()),
+            heights: HexMap::new(shape.clone()),
+            cliffs: HexMap::new(shape.clone()),
+            blocked: HexMap::new(shape.clone()),
+        }
+    }
+
+    fn set_tile(&mut self, pos: PosHex, tile: TileType) {
+        for pos in images(self.options.symmetry, pos) {
+            if self.tiles.is_inboard(pos) {
+                self.tiles.set_tile(pos, tile);
+            }
+        }
+    }
+
+    fn set_blocked(&mut self, pos: PosHex, is_blocked: bool) {
+        for pos in images(self.options.symmetry, pos) {
+            if self.blocked.is_inboard(pos) {
+                self.blocked.set_tile(pos, is_blocked);
+            }
+        }
+    }
+
+    fn set_cliff(&mut self, pos: PosHex, is_cliff: bool) {
+        for pos in images(self.options.symmetry, pos) {
+            if self.cliffs.is_inboard(pos) {
+                self.cliffs.set_tile(pos, is_cliff);
+            }
+        }
+    }
+
+    fn is_climbable(&self, from: PosHex, to: PosHex) -> bool {
+        let diff = self.heights.tile(to).0 - self.heights.tile(from).0;
+        diff.abs() <= MAX_CLIMB.0
+    }
+
+    fn random_pos(&mut self) -> PosHex {
+        let min = self.tiles.shape().min();
+        let max = self.tiles.shape().max();
+        loop {
+            let pos = PosHex {
+                q: self.rng.gen_range(min.q, max.q + 1),
+                r: self.rng.gen_range(min.r, max.r + 1),
+            };
+            if self.tiles.is_inboard(pos) {
+                return pos;
+            }
+        }
+    }
+
+    fn random_neighbor(&mut self, pos: PosHex) -> PosHex {
+        let dir = Dir::from_int(self.rng.gen_range(0, 6));
+        let neighbor = Dir::get_neighbor_pos(pos, dir);
+        if self.tiles.is_inboard(neighbor) {
+            neighbor
+        } else {
+            pos
+        }
+    }
+
+    /// Generates a random-walk cluster of positions.
+    fn cluster(&mut self) -> Vec<PosHex> {
+        let size = self.rng.gen_range(1, self.options.cluster_size.max(1) + 1);
+        let mut pos = self.random_pos();
+        let mut cluster = vec![pos];
+        for _ in 1..size {
+            pos = self.random_neighbor(pos);
+            cluster.push(pos);
+        }
+        cluster
+    }
+
+    fn clusters_count(&self, density: f32) -> i32 {
+        let average_cluster_size = (self.options.cluster_size.max(1) + 1) as f32 / 2.0;
+        let count = self.tiles.shape().tiles_count() as f32 * density / average_cluster_size;
+        count.round() as i32
+    }
+
+    fn add_rocks(&mut self) {
+        let count = self.clusters_count(self.options.rocks_density);
+        for _ in 0..count {
+            for pos in self.cluster() {
+                self.set_tile(pos, TileType::Rocks);
+            }
+        }
+    }
+
+    fn add_hills(&mut self) {
+        let count = self.clusters_count(self.options.hills_density);
+        for _ in 0..count {
+            let mut cluster = self.cluster();
+            cluster.sort_by_key(|pos| (pos.q, pos.r));
+            cluster.dedup();
+            let images: Vec<PosHex> = cluster
+                .into_iter()
+                .flat_map(|pos| images(self.options.symmetry, pos))
+                .filter(|&pos| self.heights.is_inboard(pos))
+                .collect();
+            for pos in images {
+                let height = self.heights.tile(pos).0 + 1;
+                let height = Height(height.min(self.options.max_height.0));
+                self.heights.set_tile(pos, height);
+            }
+        }
+    }
+
+    fn add_cliffs(&mut self) {
+        let count = self.clusters_count(self.options.cliffs_density);
+        for _ in 0..count {
+            for pos in self.cluster() {
+                self.set_cliff(pos, true);
+            }
+        }
+        let max_height = self.options.max_height;
+        let shape = self.heights.shape().clone();
+        for pos in shape.iter() {
+            if self.cliffs.tile(pos) {
+                self.heights.set_tile(pos, max_height);
+            }
+        }
+    }
+
+    /// Lowers the tiles (except for the cliffs) until all their slopes are walkable.
+    fn smooth_heights(&mut self) {
+        let shape = self.heights.shape().clone();
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            for pos in shape.iter() {
+                if self.cliffs.tile(pos) {
+                    continue;
+                }
+                let height = self.heights.tile(pos);
+                for dir in dirs() {
+                    let neighbor = Dir::get_neighbor_pos(pos, dir);
+                    if !self.heights.is_inboard(neighbor) {
+                        continue;
+                    }
+                    let max_height = Height(self.heights.tile(neighbor).0 + MAX_CLIMB.0);
+                    if height > max_height {
+                        self.heights.set_tile(pos, max_height);
+                        is_changed = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_obstacles(&mut self) {
+        let count = self.clusters_count(self.options.obstacle_density);
+        for _ in 0..count {
+            for pos in self.cluster() {
+                self.set_blocked(pos, true);
+            }
+        }
+    }
+
+    /// Tiles of the row `r` that lie on the line between the spawn sectors.
+    fn wall_row(&self, r: i32) -> Vec<PosHex> {
+        let shape = self.tiles.shape();
+        (shape.min().q..shape.max().q + 1)
+            .map(|q| PosHex { q, r })
+            .filter(|&pos| (pos.q * 2 + pos.r).abs() <= 1 && self.tiles.is_inboard(pos))
+            .collect()
+    }
+
+    /// Builds a wall of obstacles between the spawn sectors
+    /// with `chokepoints` evenly distributed passages.
+    fn add_wall(&mut self) {
+        let chokepoints = self.options.chokepoints;
+        if chokepoints <= 0 {
+            return;
+        }
+        let min_r = self.tiles.shape().min().r;
+        let max_r = self.tiles.shape().max().r;
+        let step = (max_r - min_r + 1) as f32 / chokepoints as f32;
+        let passages: Vec<i32> = (0..chokepoints)
+            .map(|i| min_r + (step * (i as f32 + 0.5)) as i32)
+            .collect();
+        for r in min_r..max_r + 1 {
+            let is_blocked = !passages.contains(&r);
+            for pos in self.wall_row(r) {
+                self.set_blocked(pos, is_blocked);
+            }
+        }
+    }
+
+    /// Marks all free tiles that are reachable from `start` without climbing the cliffs.
+    fn flood_fill(&self, start: PosHex) -> HexMap<bool> {
+        let mut reached = HexMap::new(self.tiles.shape().clone());
+        let mut queue = VecDeque::new();
+        reached.set_tile(start, true);
+        queue.push_back(start);
+        while let Some(pos) = queue.pop_front() {
+            for dir in dirs() {
+                let neighbor = Dir::get_neighbor_pos(pos, dir);
+                if !self.tiles.is_inboard(neighbor) || self.blocked.tile(neighbor) {
+                    continue;
+                }
+                if !reached.tile(neighbor) && self.is_climbable(pos, neighbor) {
+                    reached.set_tile(neighbor, true);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Removes obstacles and cliffs on the shortest path from `from` to any reached tile.
+    ///
+    /// The path is smoothed, so a cliff gets a ramp.
+    fn carve_passage(&mut self, reached: &HexMap<bool>, from: PosHex) {
+        let mut parents: HexMap<Option<Dir>> = HexMap::new(self.tiles.shape().clone());
+        let mut visited: HexMap<bool> = HexMap::new(self.tiles.shape().clone());
+        let mut queue = VecDeque::new();
+        visited.set_tile(from, true);
+        queue.push_back(from);
+        let mut destination = None;
+        while let Some(pos) = queue.pop_front() {
+            if reached.tile(pos) {
+                destination = Some(pos);
+                break;
+            }
+            for dir in dirs() {
+                let neighbor = Dir::get_neighbor_pos(pos, dir);
+                if !self.tiles.is_inboard(neighbor) || visited.tile(neighbor) {
+                    continue;
+                }
+                visited.set_tile(neighbor, true);
+                parents.set_tile(neighbor, Some(Dir::get_dir_from_to(neighbor, pos)));
+                queue.push_back(neighbor);
+            }
+        }
+        let mut pos = destination.expect("Reached area must not be empty");
+        while let Some(dir) = parents.tile(pos) {
+            self.set_blocked(pos, false);
+            self.set_cliff(pos, false);
+            pos = Dir::get_neighbor_pos(pos, dir);
+        }
+        self.set_cliff(from, false);
+        self.smooth_heights();
+    }
+
+    /// Guarantees that all free tiles (and so both spawn sectors)
+    /// belong to one connected and walkable area.
+    fn connect_areas(&mut self) {
+        if self.tiles.iter().all(|pos| self.blocked.tile(pos)) {
+            let pos = self.tiles.iter().next().expect("Empty map");
+            self.set_blocked(pos, false);
+        }
+        loop {
+            let start = self.tiles
+                .iter()
+                .find(|&pos| !self.blocked.tile(pos))
+                .unwrap();
+            let reached = self.flood_fill(start);
+            let unreached = self.tiles
+                .iter()
+                .find(|&pos| !self.blocked.tile(pos) && !reached.tile(pos));
+            match unreached {
+                Some(pos) => self.carve_passage(&reached, pos),
+                None => break,
+            }
+        }
+    }
+
+    fn generate(mut self) -> GeneratedMap {
+        self.add_rocks();
+        self.add_hills();
+        self.add_cliffs();
+        self.smooth_heights();
+        self.add_obstacles();
+        self.add_wall();
+        self.connect_areas();
+        let blocked = &self.blocked;
+        let obstacles = self.tiles.iter().filter(|&pos| blocked.tile(pos)).collect();
+        GeneratedMap {
+            tiles: self.tiles,
+            heights: self.heights,
+            obstacles,
+        }
+    }
+}
+
+pub fn generate(shape: &Shape, options: &Options) -> GeneratedMap {
+    Generator::new(shape, options).generate()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use core::map::{self, dirs, Axis, Dir, Distance, HexMap, PosHex, Shape};
+    use core::{PlayerId, MAX_CLIMB};
+    use super::{generate, is_in_spawn_sector, Error, GeneratedMap, Options, Symmetry};
+
+    fn is_climbable(map: &GeneratedMap, from: PosHex, to: PosHex) -> bool {
+        (map.heights.tile(to).0 - map.heights.tile(from).0).abs() <= MAX_CLIMB.0
+    }
+
+    fn has_cliffs(map: &GeneratedMap) -> bool {
+        map.tiles.iter().any(|pos| {
+            dirs().any(|dir| {
+                let neighbor = Dir::get_neighbor_pos(pos, dir);
+                map.tiles.is_inboard(neighbor) && !is_climbable(map, pos, neighbor)
+            })
+        })
+    }
+
+    fn is_walkable_area_connected(map: &GeneratedMap) -> bool {
+        let free: Vec<PosHex> = map.tiles.iter().filter(|&p| !map.is_obstacle(p)).collect();
+        let mut reached = HexMap::new(map.tiles.shape().clone());
+        let mut queue = VecDeque::new();
+        reached.set_tile(free[0], true);
+        queue.push_back(free[0]);
+        while let Some(pos) = queue.pop_front() {
+            for dir in dirs() {
+                let neighbor = Dir::get_neighbor_pos(pos, dir);
+                if !map.tiles.is_inboard(neighbor) || map.is_obstacle(neighbor)
+                    || reached.tile(neighbor) || !is_climbable(map, pos, neighbor)
+                {
+                    continue;
+                }
+                reached.set_tile(neighbor, true);
+                queue.push_back(neighbor);
+            }
+        }
+        free.into_iter().all(|pos| reached.tile(pos))
+    }
+
+    #[test]
+    fn cliffs_do_not_split_the_map() {
+        let shape = Shape::hexagon(Distance(5));
+        let mut cliffs_count = 0;
+        for seed in 0..30 {
+            let options = Options {
+                seed,
+                chokepoints: (seed % 3) as i32,
+                ..Default::default()
+            };
+            let map = generate(&shape, &options);
+            assert!(is_walkable_area_connected(&map), "seed {}:\n{}", seed, map);
+            if has_cliffs(&map) {
+                cliffs_count += 1;
+            }
+        }
+        assert!(cliffs_count > 0);
+    }
+
+    #[test]
+    fn no_cliffs_without_cliffs_density() {
+        let shape = Shape::hexagon(Distance(5));
+        for seed in 0..10 {
+            let options = Options {
+                seed,
+                cliffs_density: 0.0,
+                ..Default::default()
+            };
+            assert!(!has_cliffs(&generate(&shape, &options)));
+        }
+    }
+
+    fn is_same_map(a: &GeneratedMap, b: &GeneratedMap) -> bool {
+        a.obstacles == b.obstacles && a.tiles.iter().all(|pos| {
+            a.tiles.tile(pos) == b.tiles.tile(pos) && a.heights.tile(pos) == b.heights.tile(pos)
+        })
+    }
+
+    #[test]
+    fn same_seed_gives_same_map() {
+        let shape = Shape::hexagon(Distance(5));
+        for seed in 0..5 {
+            let options = Options {
+                seed,
+                chokepoints: 2,
+                ..Default::default()
+            };
+            let a = generate(&shape, &options);
+            let b = generate(&shape, &options);
+            assert!(is_same_map(&a, &b), "seed {}:\n{}\n{}", seed, a, b);
+        }
+        let other_options = Options {
+            seed: 1,
+            ..Default::default()
+        };
+        let a = generate(&shape, &Options::default());
+        let b = generate(&shape, &other_options);
+        assert!(!is_same_map(&a, &b));
+    }
+
+    #[test]
+    fn spawn_sectors_are_symmetric() {
+        let shape = Shape::hexagon(Distance(5));
+        let origin = PosHex { q: 0, r: 0 };
+        let image = |symmetry, pos| match symmetry {
+            Symmetry::Point => map::rotate(pos, origin, 3),
+            Symmetry::Mirror => map::reflect(pos, origin, Axis::R),
+            Symmetry::None => unreachable!(),
+        };
+        for &symmetry in &[Symmetry::Point, Symmetry::Mirror] {
+            for seed in 0..10 {
+                let options = Options {
+                    seed,
+                    symmetry,
+                    ..Default::default()
+                };
+                let map = generate(&shape, &options);
+                for pos in shape.iter() {
+                    if !is_in_spawn_sector(pos, PlayerId(0)).unwrap() {
+                        continue;
+                    }
+                    let other = image(symmetry, pos);
+                    assert!(is_in_spawn_sector(other, PlayerId(1)).unwrap());
+                    assert_eq!(map.tiles.tile(pos), map.tiles.tile(other));
+                    assert_eq!(map.heights.tile(pos), map.heights.tile(other));
+                    assert_eq!(map.is_obstacle(pos), map.is_obstacle(other));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wall_has_the_requested_number_of_chokepoints() {
+        let shape = Shape::hexagon(Distance(5));
+        let is_wall = |pos: PosHex| (pos.q * 2 + pos.r).abs() <= 1;
+        for chokepoints in 1..5 {
+            let options = Options {
+                chokepoints,
+                obstacle_density: 0.0,
+                cliffs_density: 0.0,
+                ..Default::default()
+            };
+            let map = generate(&shape, &options);
+            // Rows of the wall that have a passage
+            let mut passages: Vec<i32> = shape
+                .iter()
+                .filter(|&pos| is_wall(pos) && !map.is_obstacle(pos))
+                .map(|pos| pos.r)
+                .collect();
+            passages.dedup();
+            assert_eq!(passages.len(), chokepoints as usize, "\n{}", map);
+            assert!(map.obstacles.iter().all(|&pos| is_wall(pos)));
+        }
+    }
+
+    #[test]
+    fn only_two_players_have_spawn_sectors() {
+        let pos = PosHex { q: -3, r: 0 };
+        assert_eq!(is_in_spawn_sector(pos, PlayerId(0)), Ok(true));
+        assert_eq!(is_in_spawn_sector(pos, PlayerId(1)), Ok(false));
+        assert_eq!(
+            is_in_spawn_sector(pos, PlayerId(2)),
+            Err(Error::NoSpawnSector(PlayerId(2)))
+        );
+    }
+}
@@ -1,15 +1,22 @@
 use std::default::Default;
+use serde::{Deserialize, Deserializer};
+use rancor::{GenerationalId, Join, SparseSetStorage, VecStorage};
 use core::map::{HexMap, PosHex};
 use core::movement::MovePoints;
 
 pub use core::execute::execute;
 pub use core::check::check;
+pub use core::prototype::Prototypes;
 
 pub mod command;
 pub mod event;
 pub mod movement;
 pub mod effect;
 pub mod map;
+pub mod mapgen;
+pub mod scenario;
+pub mod prototype;
+pub mod tiled;
 pub mod execute;
 pub mod component;
 
@@ -18,12 +25,23 @@ mod check;
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct PlayerId(pub i32); // TODO: make field private
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct ObjId(i32);
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjId {
+    index: u32,
+    generation: u32,
+}
+
+impl GenerationalId for ObjId {
+    fn new(index: u32, generation: u32) -> Self {
+        ObjId { index, generation }
+    }
 
-impl Default for ObjId {
-    fn default() -> Self {
-        ObjId(0)
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn generation(&self) -> u32 {
+        self.generation
     }
 }
 
@@ -40,34 +58,43 @@ pub struct Moves(pub i32);
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Jokers(pub i32);
 
-#[derive(Clone, Copy, Debug)]
+/// Elevation of a tile
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Height(pub i32);
+
+/// Tiles with a bigger height difference can't be walked between.
+pub const MAX_CLIMB: Height = Height(1);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum TileType {
+    #[default]
     Plain,
     Rocks,
 }
 
-impl Default for TileType {
-    fn default() -> Self {
-        TileType::Plain
-    }
-}
-
+// Deterministic storages: the AI and the object creation order
+// must not depend on the `HashMap`'s random iteration order.
 rancor_storage!(Parts<ObjId>: {
-    strength: component::Strength,
-    pos: component::Pos,
-    meta: component::Meta,
-    belongs_to: component::BelongsTo,
-    agent: component::Agent,
-    blocker: component::Blocker,
-});
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Prototypes(pub HashMap<String, Vec<component::Component>>);
-
-#[derive(Clone, Debug)]
+    strength: Strength(component::Strength) => VecStorage,
+    pos: Pos(component::Pos) => VecStorage,
+    meta: Meta(component::Meta) => VecStorage,
+    belongs_to: BelongsTo(component::BelongsTo) => SparseSetStorage,
+    agent: Agent(component::Agent) => SparseSetStorage,
+    blocker: Blocker(component::Blocker) => SparseSetStorage,
+    debris: Debris(component::Debris) => SparseSetStorage,
+} with #[derive(Clone, Debug, Serialize, Deserialize)] enum Component / ComponentKind);
+
+/// The spatial index is not serialized: it's rebuilt on deserialization.
+#[derive(Clone, Debug, Serialize)]
 pub struct State {
     parts: Parts,
     map: HexMap<TileType>,
+    heights: HexMap<Height>,
+
+    /// Ids of the objects at every tile (see `event::apply`)
+    #[serde(skip_serializing)]
+    spatial_index: HexMap<Vec<ObjId>>,
+
     player_id: PlayerId,
     players_count: i32,
     prototypes: Prototypes,
@@ -76,11 +103,16 @@ pub struct State {
 impl State {
     pub fn new(prototypes: Prototypes) -> Self {
         let radius = map::Distance(5); // TODO: pass `Options` struct
+        let shape = map::Shape::hexagon(radius);
+        let mut parts = Parts::new();
+        parts.enable_change_tracking();
         Self {
-            map: HexMap::new(radius),
+            map: HexMap::new(shape.clone()),
+            heights: HexMap::new(shape.clone()),
+            spatial_index: HexMap::new(shape),
             player_id: PlayerId(0),
             players_count: 2, // TODO: Read from the `Options` struct
-            parts: Parts::new(),
+            parts,
             prototypes,
         }
     }
@@ -96,33 +128,176 @@ impl State {
     pub fn map(&self) -> &HexMap<TileType> {
         &self.map
     }
+
+    pub fn heights(&self) -> &HexMap<Height> {
+        &self.heights
+    }
+
+    pub fn prototypes(&self) -> &Prototypes {
+        &self.prototypes
+    }
+}
+
+/// Serialized fields of `State`
+#[derive(Deserialize)]
+struct StateData {
+    parts: Parts,
+    map: HexMap<TileType>,
+    heights: HexMap<Height>,
+    player_id: PlayerId,
+    players_count: i32,
+    prototypes: Prototypes,
 }
 
-pub fn belongs_to(state: &State, player_id: PlayerId, id: ObjId) -> bool {
-    state.parts.belongs_to.get(id).0 == player_id
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = StateData::deserialize(deserializer)?;
+        let mut state = Self {
+            spatial_index: HexMap::new(data.map.shape().clone()),
+            parts: data.parts,
+            map: data.map,
+            heights: data.heights,
+            player_id: data.player_id,
+            players_count: data.players_count,
+            prototypes: data.prototypes,
+        };
+        rebuild_spatial_index(&mut state);
+        Ok(state)
+    }
 }
 
 pub fn object_ids_at(state: &State, pos: PosHex) -> Vec<ObjId> {
-    let ids = state.parts().agent.ids();
-    ids.filter(|&id| state.parts.pos.get(id).0 == pos).collect()
+    if !state.spatial_index.is_inboard(pos) {
+        return Vec::new();
+    }
+    state.spatial_index.tile_ref(pos).clone()
+}
+
+fn add_to_spatial_index(state: &mut State, id: ObjId, pos: PosHex) {
+    let ids = state.spatial_index.tile_mut(pos);
+    assert!(!ids.contains(&id));
+    ids.push(id);
+}
+
+fn remove_from_spatial_index(state: &mut State, id: ObjId, pos: PosHex) {
+    let ids = state.spatial_index.tile_mut(pos);
+    let index = ids.iter().position(|&other_id| other_id == id).unwrap();
+    ids.remove(index);
+}
+
+/// Inserts the component keeping the spatial index in sync.
+pub fn insert_component(state: &mut State, id: ObjId, component: Component) {
+    if let Component::Pos(ref pos) = component {
+        add_to_spatial_index(state, id, pos.0);
+    }
+    state.parts.insert_component(id, component);
+}
+
+/// Fills the index from scratch (e.g. after the map's shape was changed).
+fn rebuild_spatial_index(state: &mut State) {
+    state.spatial_index = HexMap::new(state.map.shape().clone());
+    let ids: Vec<_> = state.parts.pos.ids().collect();
+    for id in ids {
+        let pos = state.parts.pos.get(id).0;
+        add_to_spatial_index(state, id, pos);
+    }
+}
+
+/// Checks that the spatial index contains exactly the objects from `parts.pos`.
+pub fn is_spatial_index_consistent(state: &State) -> bool {
+    let parts = state.parts();
+    let indexed_count: usize = state
+        .spatial_index
+        .iter()
+        .map(|pos| state.spatial_index.tile_ref(pos).len())
+        .sum();
+    indexed_count == parts.pos.len() && parts.pos.ids().all(|id| {
+        let pos = parts.pos.get(id).0;
+        state.spatial_index.is_inboard(pos) && state.spatial_index.tile_ref(pos).contains(&id)
+    })
+}
+
+pub fn agent_id_at(state: &State, pos: PosHex) -> Option<ObjId> {
+    let ids = object_ids_at(state, pos);
+    ids.into_iter()
+        .find(|&id| state.parts.agent.get_opt(id).is_some())
+}
+
+/// Non-agent objects with `Strength` (barricades, doors, etc)
+pub fn destructible_id_at(state: &State, pos: PosHex) -> Option<ObjId> {
+    let parts = state.parts();
+    let ids = object_ids_at(state, pos);
+    ids.into_iter().find(|&id| {
+        parts.agent.get_opt(id).is_none() && parts.strength.get_opt(id).is_some()
+    })
 }
 
 pub fn players_agent_ids(state: &State, player_id: PlayerId) -> Vec<ObjId> {
-    let ids = state.parts().agent.ids();
-    ids.filter(|&id| belongs_to(state, player_id, id)).collect()
+    let parts = state.parts();
+    (&parts.agent, &parts.belongs_to)
+        .join()
+        .filter(|&(_, _, belongs_to)| belongs_to.0 == player_id)
+        .map(|(id, _, _)| id)
+        .collect()
 }
 
 pub fn enemy_agent_ids(state: &State, player_id: PlayerId) -> Vec<ObjId> {
-    let ids = state.parts().agent.ids();
-    ids.filter(|&id| !belongs_to(state, player_id, id))
+    let parts = state.parts();
+    (&parts.agent, &parts.belongs_to)
+        .join()
+        .filter(|&(_, _, belongs_to)| belongs_to.0 != player_id)
+        .map(|(id, _, _)| id)
         .collect()
 }
 
+/// How much higher `a` is than `b`
+pub fn height_diff(state: &State, a: PosHex, b: PosHex) -> Height {
+    let heights = state.heights();
+    Height(heights.tile(a).0 - heights.tile(b).0)
+}
+
 pub fn is_tile_blocked(state: &State, pos: PosHex) -> bool {
-    for id in state.parts.blocker.ids() {
-        if state.parts.pos.get(id).0 == pos {
-            return true;
+    if !state.spatial_index.is_inboard(pos) {
+        return false;
+    }
+    let ids = state.spatial_index.tile_ref(pos);
+    ids.iter()
+        .any(|&id| state.parts.blocker.get_opt(id).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use ron;
+    use core::component::{Blocker, Component, Pos};
+    use core::effect;
+    use core::map::PosHex;
+    use core::{Prototypes, State};
+
+    #[test]
+    fn spatial_index_is_rebuilt_on_deserialization() {
+        let mut state = State::new(Prototypes::default());
+        let pos = PosHex { q: 1, r: -1 };
+        let other_pos = PosHex { q: 0, r: 0 };
+        let mut ids = Vec::new();
+        for &(pos, is_blocker) in &[(pos, true), (pos, false), (other_pos, false)] {
+            let id = state.parts.alloc_id();
+            super::insert_component(&mut state, id, Component::Pos(Pos(pos)));
+            if is_blocker {
+                super::insert_component(&mut state, id, Component::Blocker(Blocker));
+            }
+            ids.push(id);
         }
+        effect::apply_kill(&mut state, ids[2]);
+        let s = ron::ser::to_string(&state).unwrap();
+        assert!(!s.contains("spatial_index"));
+        let restored: State = ron::de::from_str(&s).unwrap();
+        assert!(super::is_spatial_index_consistent(&restored));
+        let mut restored_ids = super::object_ids_at(&restored, pos);
+        restored_ids.sort();
+        assert_eq!(restored_ids, vec![ids[0], ids[1]]);
+        assert!(super::object_ids_at(&restored, other_pos).is_empty());
+        assert!(super::is_tile_blocked(&restored, pos));
+        assert!(!super::is_tile_blocked(&restored, other_pos));
+        assert!(restored.parts().is_change_tracking_enabled());
     }
-    false
 }
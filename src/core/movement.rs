@@ -1,8 +1,8 @@
 use std::collections::VecDeque;
 use std::slice::Windows;
 use core;
-use core::map::{dirs, Dir, Distance, HexMap, PosHex};
-use core::{ObjId, State, TileType};
+use core::map::{dirs, Dir, HexMap, PosHex, Shape};
+use core::{ObjId, State, TileType, MAX_CLIMB};
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MovePoints(pub i32);
@@ -37,11 +37,18 @@ pub fn max_cost() -> MovePoints {
     MovePoints(i32::max_value())
 }
 
-pub fn tile_cost(state: &State, _: ObjId, _: PosHex, pos: PosHex) -> MovePoints {
-    match state.map().tile(pos) {
-        TileType::Plain => MovePoints(1),
-        TileType::Rocks => MovePoints(3),
-    }
+/// Moving uphill costs an additional move point for every level of height.
+pub fn tile_cost(state: &State, _: ObjId, from: PosHex, pos: PosHex) -> MovePoints {
+    let cost = match state.map().tile(pos) {
+        TileType::Plain => 1,
+        TileType::Rocks => 3,
+    };
+    let climb = core::height_diff(state, pos, from).0.max(0);
+    MovePoints(cost + climb)
+}
+
+pub fn is_passable(state: &State, from: PosHex, to: PosHex) -> bool {
+    core::height_diff(state, to, from).0.abs() <= MAX_CLIMB.0
 }
 
 #[derive(Clone, Debug)]
@@ -125,10 +132,10 @@ pub struct Pathfinder {
 }
 
 impl Pathfinder {
-    pub fn new(map_radius: Distance) -> Pathfinder {
+    pub fn new(map_shape: &Shape) -> Pathfinder {
         Pathfinder {
             queue: VecDeque::new(),
-            map: HexMap::new(map_radius),
+            map: HexMap::new(map_shape.clone()),
         }
     }
 
@@ -159,23 +166,21 @@ impl Pathfinder {
     }
 
     fn clean_map(&mut self) {
-        for pos in self.map.iter() {
-            let tile = Tile {
-                cost: max_cost(),
-                parent_dir: None,
-            };
-            self.map.set_tile(pos, tile);
-        }
+        let tile = Tile {
+            cost: max_cost(),
+            parent_dir: None,
+        };
+        self.map.fill(tile);
     }
 
     fn try_to_push_neighbors(&mut self, state: &State, id: ObjId, pos: PosHex) {
         assert!(self.map.is_inboard(pos));
         for dir in dirs() {
             let neighbor_pos = Dir::get_neighbor_pos(pos, dir);
-            if core::is_tile_blocked(state, neighbor_pos) {
+            if !self.map.is_inboard(neighbor_pos) || core::is_tile_blocked(state, neighbor_pos) {
                 continue;
             }
-            if self.map.is_inboard(neighbor_pos) {
+            if is_passable(state, pos, neighbor_pos) {
                 self.process_neighbor_pos(state, id, pos, neighbor_pos);
             }
         }
@@ -220,3 +225,48 @@ impl Pathfinder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rancor::GenerationalId;
+    use core::map::PosHex;
+    use core::{Height, ObjId, Prototypes, State};
+    use super::{is_passable, tile_cost, MovePoints};
+
+    const LOW: PosHex = PosHex { q: 0, r: 0 };
+    const HIGH: PosHex = PosHex { q: 1, r: 0 };
+
+    fn state_with_height(height: Height) -> State {
+        let mut state = State::new(Prototypes::default());
+        state.heights.set_tile(HIGH, height);
+        state
+    }
+
+    fn cost(state: &State, from: PosHex, to: PosHex) -> MovePoints {
+        tile_cost(state, ObjId::new(0, 0), from, to)
+    }
+
+    #[test]
+    fn flat_tiles() {
+        let state = state_with_height(Height(0));
+        assert!(is_passable(&state, LOW, HIGH));
+        assert_eq!(cost(&state, LOW, HIGH), MovePoints(1));
+        assert_eq!(cost(&state, HIGH, LOW), MovePoints(1));
+    }
+
+    #[test]
+    fn climbable_slope() {
+        let state = state_with_height(Height(1));
+        assert!(is_passable(&state, LOW, HIGH));
+        assert!(is_passable(&state, HIGH, LOW));
+        assert_eq!(cost(&state, LOW, HIGH), MovePoints(2));
+        assert_eq!(cost(&state, HIGH, LOW), MovePoints(1));
+    }
+
+    #[test]
+    fn cliff() {
+        let state = state_with_height(Height(2));
+        assert!(!is_passable(&state, LOW, HIGH));
+        assert!(!is_passable(&state, HIGH, LOW));
+    }
+}
@@ -0,0 +1,243 @@
+//! Object prototypes (`objects.ron`).
+//!
+//! A prototype can inherit another one with `parent`: its own `components`
+//! replace the inherited components of the same type and `overrides`
+//! change only the listed fields of the inherited components:
+//!
+//! ```text
+//! {
+//!     "swordsman": (
+//!         parent: Some("base_infantry"),
+//!         components: [Meta((name: "swordsman"))],
+//!     ),
+//!     "elite_swordsman": (
+//!         parent: Some("swordsman"),
+//!         overrides: [Strength((base_strength: Some(Strength(4)), strength: Some(Strength(4))))],
+//!     ),
+//! }
+//! ```
+//!
+//! `Create` commands can apply their own overrides on top of that.
+//!
+//! The old format where a prototype is just a list of its components
+//! (`"imp": [Meta((name: "imp")), ...]`) is still accepted.
+
+use std::collections::HashMap;
+use std::fmt;
+use ron;
+use core::component::{Component, ComponentOverride};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    Parse(String),
+    UnknownPrototype(String),
+    InheritanceCycle(String),
+    NothingToOverride {
+        prototype: String,
+        component: &'static str,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Parse(ref s) => write!(f, "Can't parse the prototypes: {}", s),
+            Error::UnknownPrototype(ref s) => write!(f, "Unknown prototype '{}'", s),
+            Error::InheritanceCycle(ref s) => {
+                write!(f, "Prototype '{}' inherits from itself", s)
+            }
+            Error::NothingToOverride {
+                ref prototype,
+                component,
+            } => write!(
+                f,
+                "Prototype '{}' overrides a missing '{}' component",
+                prototype, component
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Prototype {
+    pub parent: Option<String>,
+    pub components: Vec<Component>,
+    pub overrides: Vec<ComponentOverride>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Prototypes(pub HashMap<String, Prototype>);
+
+/// Parses and validates the prototypes.
+///
+/// Falls back to the old format if the data can't be parsed as the new one.
+pub fn load(data: &str) -> Result<Prototypes, Error> {
+    let prototypes = match ron::de::from_str(data) {
+        Ok(prototypes) => Prototypes(prototypes),
+        Err(err) => match ron::de::from_str::<HashMap<String, Vec<Component>>>(data) {
+            Ok(old) => from_old_format(old),
+            Err(_) => return Err(Error::Parse(err.to_string())),
+        },
+    };
+    prototypes.validate()?;
+    Ok(prototypes)
+}
+
+fn from_old_format(old: HashMap<String, Vec<Component>>) -> Prototypes {
+    let prototypes = old.into_iter()
+        .map(|(name, components)| {
+            let prototype = Prototype {
+                components,
+                ..Default::default()
+            };
+            (name, prototype)
+        })
+        .collect();
+    Prototypes(prototypes)
+}
+
+impl Prototypes {
+    /// All the components of the prototype including the inherited ones
+    pub fn components(&self, name: &str) -> Result<Vec<Component>, Error> {
+        self.resolve(name, &mut Vec::new())
+    }
+
+    /// Same as `components`, but with the extra overrides applied.
+    pub fn components_with_overrides(
+        &self,
+        name: &str,
+        overrides: &[ComponentOverride],
+    ) -> Result<Vec<Component>, Error> {
+        let mut components = self.components(name)?;
+        apply_overrides(name, &mut components, overrides)?;
+        Ok(components)
+    }
+
+    /// The prototype itself and then its ancestors, the nearest first.
+    ///
+    /// Stops at an unknown prototype or at an inheritance cycle.
+    pub fn lineage<'a>(&'a self, name: &'a str) -> Vec<&'a str> {
+        let mut lineage = Vec::new();
+        let mut next = Some(name);
+        while let Some(name) = next {
+            if lineage.contains(&name) {
+                break;
+            }
+            next = match self.0.get(name) {
+                Some(prototype) => prototype.parent.as_ref().map(|s| s.as_str()),
+                None => break,
+            };
+            lineage.push(name);
+        }
+        lineage
+    }
+
+    /// Checks that every prototype can be resolved.
+    pub fn validate(&self) -> Result<(), Error> {
+        for name in self.0.keys() {
+            self.components(name)?;
+        }
+        Ok(())
+    }
+
+    fn resolve(&self, name: &str, children: &mut Vec<String>) -> Result<Vec<Component>, Error> {
+        if children.iter().any(|child| child == name) {
+            return Err(Error::InheritanceCycle(name.into()));
+        }
+        let prototype = match self.0.get(name) {
+            Some(prototype) => prototype,
+            None => return Err(Error::UnknownPrototype(name.into())),
+        };
+        children.push(name.into());
+        let mut components = match prototype.parent {
+            Some(ref parent) => self.resolve(parent, children)?,
+            None => Vec::new(),
+        };
+        for component in &prototype.components {
+            let kind = component.kind();
+            match components.iter().position(|c| c.kind() == kind) {
+                Some(i) => components[i] = component.clone(),
+                None => components.push(component.clone()),
+            }
+        }
+        apply_overrides(name, &mut components, &prototype.overrides)?;
+        Ok(components)
+    }
+}
+
+fn apply_overrides(
+    prototype: &str,
+    components: &mut [Component],
+    overrides: &[ComponentOverride],
+) -> Result<(), Error> {
+    for o in overrides {
+        let is_applied = components.iter_mut().any(|component| o.apply(component));
+        if !is_applied {
+            return Err(Error::NothingToOverride {
+                prototype: prototype.into(),
+                component: o.name(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ron;
+    use core::component::Component;
+    use super::Error;
+
+    #[test]
+    fn old_format() {
+        let s = r#"{ "imp": [Meta((name: "imp")), Blocker(())] }"#;
+        let prototypes = super::load(s).unwrap();
+        let components = prototypes.components("imp").unwrap();
+        assert_eq!(components.len(), 2);
+        match components[0] {
+            Component::Meta(ref meta) => assert_eq!(meta.name, "imp"),
+            ref component => panic!("Unexpected component: {:?}", component),
+        }
+    }
+
+    #[test]
+    fn new_format() {
+        let s = r#"{
+            "base": (components: [Meta((name: "base")), Blocker(())]),
+            "imp": (parent: Some("base"), components: [Meta((name: "imp"))]),
+        }"#;
+        let prototypes = super::load(s).unwrap();
+        let components = prototypes.components("imp").unwrap();
+        assert_eq!(components.len(), 2);
+        match components[0] {
+            Component::Meta(ref meta) => assert_eq!(meta.name, "imp"),
+            ref component => panic!("Unexpected component: {:?}", component),
+        }
+        assert_eq!(prototypes.lineage("imp"), vec!["imp", "base"]);
+    }
+
+    #[test]
+    fn parse_error() {
+        match super::load("{ \"imp\": 1 }") {
+            Err(Error::Parse(_)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn invalid_prototypes() {
+        let s = r#"{ "imp": (parent: Some("demon")) }"#;
+        assert_eq!(
+            super::load(s).unwrap_err(),
+            Error::UnknownPrototype("demon".into())
+        );
+    }
+
+    #[test]
+    fn lineage_stops_at_cycle() {
+        let s = r#"{ "a": (parent: Some("b")), "b": (parent: Some("a")) }"#;
+        let prototypes = super::Prototypes(ron::de::from_str(s).unwrap());
+        assert_eq!(prototypes.lineage("a"), vec!["a", "b"]);
+    }
+}
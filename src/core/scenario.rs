@@ -0,0 +1,119 @@
+use std::fmt;
+use core::{Height, PlayerId, TileType};
+use core::command;
+use core::map::{HexMap, PosHex, Shape};
+
+/// Tiles where the units of some player can be placed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SpawnZone {
+    pub player_id: PlayerId,
+    pub tiles: Vec<PosHex>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The heights layer doesn't match the tile map
+    HeightsShapeMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::HeightsShapeMismatch => {
+                write!(f, "The heights layer's shape doesn't match the map's one")
+            }
+        }
+    }
+}
+
+/// A hand-made map with objects and spawn zones (see `screen::Editor`)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Scenario {
+    pub map: HexMap<TileType>,
+    pub heights: HexMap<Height>,
+    pub objects: Vec<command::Create>,
+    pub spawn_zones: Vec<SpawnZone>,
+}
+
+impl Scenario {
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            map: HexMap::new(shape.clone()),
+            heights: HexMap::new(shape),
+            objects: Vec::new(),
+            spawn_zones: Vec::new(),
+        }
+    }
+
+    /// Checks what a hand-edited file can break.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.heights.shape() != self.map.shape() {
+            return Err(Error::HeightsShapeMismatch);
+        }
+        Ok(())
+    }
+
+    pub fn object_at(&self, pos: PosHex) -> Option<&command::Create> {
+        self.objects.iter().find(|object| object.pos == pos)
+    }
+
+    pub fn add_object(&mut self, object: command::Create) {
+        assert!(self.map.is_inboard(object.pos));
+        assert!(self.object_at(object.pos).is_none());
+        self.objects.push(object);
+    }
+
+    pub fn remove_object_at(&mut self, pos: PosHex) {
+        self.objects.retain(|object| object.pos != pos);
+    }
+
+    pub fn spawn_zone(&self, player_id: PlayerId) -> Option<&SpawnZone> {
+        self.spawn_zones
+            .iter()
+            .find(|zone| zone.player_id == player_id)
+    }
+
+    pub fn is_spawn_tile(&self, player_id: PlayerId, pos: PosHex) -> bool {
+        match self.spawn_zone(player_id) {
+            Some(zone) => zone.tiles.contains(&pos),
+            None => false,
+        }
+    }
+
+    /// Adds the tile to the player's spawn zone or removes it from there.
+    pub fn toggle_spawn_tile(&mut self, player_id: PlayerId, pos: PosHex) {
+        assert!(self.map.is_inboard(pos));
+        if self.spawn_zone(player_id).is_none() {
+            self.spawn_zones.push(SpawnZone {
+                player_id,
+                tiles: Vec::new(),
+            });
+        }
+        let zone = self.spawn_zones
+            .iter_mut()
+            .find(|zone| zone.player_id == player_id)
+            .unwrap();
+        if zone.tiles.contains(&pos) {
+            zone.tiles.retain(|&tile| tile != pos);
+        } else {
+            zone.tiles.push(pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::map::{Distance, HexMap, Shape};
+    use super::{Error, Scenario};
+
+    #[test]
+    fn heights_must_match_the_map() {
+        let mut scenario = Scenario::new(Shape::hexagon(Distance(2)));
+        assert_eq!(scenario.validate(), Ok(()));
+        scenario.heights = HexMap::new(Shape::hexagon(Distance(1)));
+        assert_eq!(scenario.validate(), Err(Error::HeightsShapeMismatch));
+        let shape = Shape::hexagon(Distance(2)).filter(|pos| pos.q != 0);
+        scenario.heights = HexMap::new(shape);
+        assert_eq!(scenario.validate(), Err(Error::HeightsShapeMismatch));
+    }
+}
@@ -0,0 +1,624 @@
+//! Importer of hex maps made in the [Tiled](http://www.mapeditor.org) editor.
+//!
+//! Supported: "hexagonal" and "staggered" orientations with the `y` stagger axis
+//! (our hexes are pointy-topped), embedded tilesets, CSV-encoded tile layers
+//! and object layers.
+//!
+//! Tile types are taken from the `tile_type` property (or the `type` attribute)
+//! of the tileset's tiles: "plain" or "rocks". Every tile used in the layers
+//! must have a type. Cells that are empty in all tile layers are not the part
+//! of the map.
+//!
+//! Objects are converted into `Create` commands: the prototype is taken from
+//! the `prototype` property (or the object's `type`) and the owner
+//! from the optional `owner` property (a player's index).
+
+use std::collections::HashMap;
+use std::fmt;
+use xml::reader::{EventReader, XmlEvent};
+use core::{PlayerId, Prototypes, TileType};
+use core::command;
+use core::map::{HexMap, PosHex, Shape};
+use core::scenario::Scenario;
+
+/// Tiled stores the flip flags in the highest bits of a gid.
+const GID_MASK: u32 = 0x1FFF_FFFF;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Xml(String),
+    NoMapElement,
+    MissingAttribute { element: String, attribute: String },
+    BadAttribute { attribute: String, value: String },
+    UnsupportedOrientation(String),
+    UnsupportedStaggerAxis(String),
+    UnsupportedEncoding(String),
+    ExternalTileset(String),
+    EmptyMap,
+    UnknownTileType(String),
+    UnknownTile(u32),
+    UnknownPrototype(String),
+    ObjectWithoutPrototype(u32),
+    ObjectIsOutsideTheMap(u32),
+    TileIsOccupied(u32),
+    BadOwner(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Xml(ref s) => write!(f, "Can't parse the XML: {}", s),
+            Error::NoMapElement => write!(f, "No <map> element"),
+            Error::MissingAttribute {
+                ref element,
+                ref attribute,
+            } => write!(f, "<{}> has no '{}' attribute", element, attribute),
+            Error::BadAttribute {
+                ref attribute,
+                ref value,
+            } => write!(f, "Bad value of the '{}' attribute: '{}'", attribute, value),
+            Error::UnsupportedOrientation(ref s) => write!(
+                f,
+                "Unsupported map orientation '{}' (only 'hexagonal' and 'staggered' are supported)",
+                s
+            ),
+            Error::UnsupportedStaggerAxis(ref s) => write!(
+                f,
+                "Unsupported stagger axis '{}' (only 'y' - pointy-topped hexes - is supported)",
+                s
+            ),
+            Error::UnsupportedEncoding(ref s) => write!(
+                f,
+                "Unsupported layer encoding '{}' (only 'csv' is supported)",
+                s
+            ),
+            Error::ExternalTileset(ref s) => {
+                write!(f, "External tilesets are not supported: '{}'", s)
+            }
+            Error::EmptyMap => write!(f, "The map has no tiles"),
+            Error::UnknownTileType(ref s) => write!(f, "Unknown tile type '{}'", s),
+            Error::UnknownTile(gid) => write!(f, "Tile {} has no tile type", gid),
+            Error::UnknownPrototype(ref s) => write!(f, "Unknown prototype '{}'", s),
+            Error::ObjectWithoutPrototype(id) => write!(f, "Object #{} has no prototype", id),
+            Error::ObjectIsOutsideTheMap(id) => write!(f, "Object #{} is outside the map", id),
+            Error::TileIsOccupied(id) => {
+                write!(f, "Object #{} is placed on an occupied tile", id)
+            }
+            Error::BadOwner(ref s) => write!(f, "Bad owner '{}'", s),
+        }
+    }
+}
+
+/// A minimal DOM-like representation of the XML file
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: HashMap<String, String>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn children<'a>(&'a self, name: &'a str) -> Box<Iterator<Item = &'a Element> + 'a> {
+        Box::new(self.children.iter().filter(move |e| e.name == name))
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|e| e.name == name)
+    }
+
+    fn attr_opt(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(|s| s.as_str())
+    }
+
+    fn attr(&self, name: &str) -> Result<&str, Error> {
+        self.attr_opt(name).ok_or_else(|| Error::MissingAttribute {
+            element: self.name.clone(),
+            attribute: name.into(),
+        })
+    }
+
+    fn attr_parse<T: ::std::str::FromStr>(&self, name: &str) -> Result<T, Error> {
+        let value = self.attr(name)?;
+        value.trim().parse().map_err(|_| Error::BadAttribute {
+            attribute: name.into(),
+            value: value.into(),
+        })
+    }
+
+    fn property(&self, name: &str) -> Option<&str> {
+        let properties = match self.child("properties") {
+            Some(properties) => properties,
+            None => return None,
+        };
+        properties
+            .children("property")
+            .find(|p| p.attr_opt("name") == Some(name))
+            .and_then(|p| p.attr_opt("value"))
+    }
+}
+
+fn parse_xml(data: &str) -> Result<Element, Error> {
+    let mut stack: Vec<Element> = vec![Element::default()];
+    for event in EventReader::from_str(data) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let attributes = attributes
+                    .into_iter()
+                    .map(|a| (a.name.local_name, a.value))
+                    .collect();
+                stack.push(Element {
+                    name: name.local_name,
+                    attributes,
+                    ..Default::default()
+                });
+            }
+            Ok(XmlEvent::EndElement { .. }) => {
+                let element = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(element);
+            }
+            Ok(XmlEvent::Characters(s)) => stack.last_mut().unwrap().text.push_str(&s),
+            Ok(_) => {}
+            Err(err) => return Err(Error::Xml(err.to_string())),
+        }
+    }
+    let root = stack.pop().unwrap();
+    root.children
+        .into_iter()
+        .find(|e| e.name == "map")
+        .ok_or(Error::NoMapElement)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StaggerIndex {
+    Odd,
+    Even,
+}
+
+/// Pixel geometry of the map's rows and columns
+#[derive(Clone, Copy, Debug)]
+struct Layout {
+    stagger_index: StaggerIndex,
+    tile_width: f32,
+    row_height: f32,
+}
+
+impl Layout {
+    fn from_map(map: &Element) -> Result<Self, Error> {
+        let orientation = map.attr("orientation")?;
+        let hex_side_length: f32 = match orientation {
+            "hexagonal" => map.attr_parse("hexsidelength")?,
+            "staggered" => 0.0,
+            _ => return Err(Error::UnsupportedOrientation(orientation.into())),
+        };
+        let stagger_axis = map.attr_opt("staggeraxis").unwrap_or("y");
+        if stagger_axis != "y" {
+            return Err(Error::UnsupportedStaggerAxis(stagger_axis.into()));
+        }
+        let stagger_index = match map.attr_opt("staggerindex").unwrap_or("odd") {
+            "odd" => StaggerIndex::Odd,
+            "even" => StaggerIndex::Even,
+            value => {
+                return Err(Error::BadAttribute {
+                    attribute: "staggerindex".into(),
+                    value: value.into(),
+                })
+            }
+        };
+        let tile_width: f32 = map.attr_parse("tilewidth")?;
+        let tile_height: f32 = map.attr_parse("tileheight")?;
+        Ok(Self {
+            stagger_index,
+            tile_width,
+            row_height: (tile_height + hex_side_length) / 2.0,
+        })
+    }
+
+    fn is_row_shifted(&self, row: i32) -> bool {
+        let is_odd = row & 1 == 1;
+        match self.stagger_index {
+            StaggerIndex::Odd => is_odd,
+            StaggerIndex::Even => !is_odd,
+        }
+    }
+
+    /// Converts Tiled's offset coordinates into axial ones.
+    ///
+    /// Tiled's rows go down and ours go up, so the map is flipped vertically.
+    fn offset_to_axial(&self, col: i32, row: i32) -> PosHex {
+        // horizontal shift of the row in half-tiles relative to the even rows
+        let shift = match self.stagger_index {
+            StaggerIndex::Odd => row & 1,
+            StaggerIndex::Even => -(row & 1),
+        };
+        let r = -row;
+        PosHex {
+            q: col - (r - shift) / 2,
+            r,
+        }
+    }
+
+    fn pixel_to_axial(&self, x: f32, y: f32) -> PosHex {
+        let row = (y / self.row_height).floor() as i32;
+        let shift = if self.is_row_shifted(row) {
+            self.tile_width / 2.0
+        } else {
+            0.0
+        };
+        let col = ((x - shift) / self.tile_width).floor() as i32;
+        self.offset_to_axial(col, row)
+    }
+}
+
+fn parse_tile_type(s: &str) -> Result<TileType, Error> {
+    match s {
+        "plain" => Ok(TileType::Plain),
+        "rocks" => Ok(TileType::Rocks),
+        _ => Err(Error::UnknownTileType(s.into())),
+    }
+}
+
+/// Maps global tile ids to tile types
+fn tile_types(map: &Element) -> Result<HashMap<u32, TileType>, Error> {
+    let mut types = HashMap::new();
+    for tileset in map.children("tileset") {
+        if let Some(source) = tileset.attr_opt("source") {
+            return Err(Error::ExternalTileset(source.into()));
+        }
+        let first_gid: u32 = tileset.attr_parse("firstgid")?;
+        for tile in tileset.children("tile") {
+            let id: u32 = tile.attr_parse("id")?;
+            let name = tile.property("tile_type").or_else(|| tile.attr_opt("type"));
+            if let Some(name) = name {
+                types.insert(first_gid + id, parse_tile_type(name)?);
+            }
+        }
+    }
+    Ok(types)
+}
+
+fn load_tiles(map: &Element, layout: &Layout) -> Result<HexMap<TileType>, Error> {
+    let types = tile_types(map)?;
+    let mut tiles = HashMap::new();
+    for layer in map.children("layer") {
+        let width: i32 = layer.attr_parse("width")?;
+        let data = match layer.child("data") {
+            Some(data) => data,
+            None => continue,
+        };
+        let encoding = data.attr_opt("encoding").unwrap_or("xml");
+        if encoding != "csv" {
+            return Err(Error::UnsupportedEncoding(encoding.into()));
+        }
+        let gids = data.text.split(',').map(|s| s.trim()).filter(|s| !s.is_empty());
+        for (i, gid) in gids.enumerate() {
+            let gid: u32 = gid.parse().map_err(|_| Error::BadAttribute {
+                attribute: "data".into(),
+                value: gid.into(),
+            })?;
+            let gid = gid & GID_MASK;
+            if gid == 0 {
+                continue;
+            }
+            let i = i as i32;
+            let pos = layout.offset_to_axial(i % width, i / width);
+            let tile = *types.get(&gid).ok_or(Error::UnknownTile(gid))?;
+            tiles.insert(pos, tile);
+        }
+    }
+    if tiles.is_empty() {
+        return Err(Error::EmptyMap);
+    }
+    let min = PosHex {
+        q: tiles.keys().map(|p| p.q).min().unwrap(),
+        r: tiles.keys().map(|p| p.r).min().unwrap(),
+    };
+    let max = PosHex {
+        q: tiles.keys().map(|p| p.q).max().unwrap(),
+        r: tiles.keys().map(|p| p.r).max().unwrap(),
+    };
+    let shape = Shape::from_fn(min, max, |pos| tiles.contains_key(&pos));
+    let mut hex_map = HexMap::new(shape);
+    for (&pos, &tile) in &tiles {
+        hex_map.set_tile(pos, tile);
+    }
+    Ok(hex_map)
+}
+
+fn parse_owner(s: &str) -> Result<Option<PlayerId>, Error> {
+    match s.trim() {
+        "" | "none" => Ok(None),
+        s => s.parse()
+            .map(|id| Some(PlayerId(id)))
+            .map_err(|_| Error::BadOwner(s.into())),
+    }
+}
+
+fn load_object(
+    object: &Element,
+    layout: &Layout,
+    prototypes: &Prototypes,
+) -> Result<command::Create, Error> {
+    let id: u32 = object.attr_parse("id")?;
+    let prototype = object
+        .property("prototype")
+        .or_else(|| object.attr_opt("type"))
+        .ok_or(Error::ObjectWithoutPrototype(id))?;
+    if !prototypes.0.contains_key(prototype) {
+        return Err(Error::UnknownPrototype(prototype.into()));
+    }
+    let owner = match object.property("owner") {
+        Some(owner) => parse_owner(owner)?,
+        None => None,
+    };
+    let x: f32 = object.attr_parse("x")?;
+    let y: f32 = object.attr_parse("y")?;
+    let width: f32 = object.attr_opt("width").unwrap_or("0").parse().unwrap_or(0.0);
+    let height: f32 = object.attr_opt("height").unwrap_or("0").parse().unwrap_or(0.0);
+    // Tile objects are anchored at the bottom-left corner, others - at the top-left one.
+    let center_y = if object.attr_opt("gid").is_some() {
+        y - height / 2.0
+    } else {
+        y + height / 2.0
+    };
+    let pos = layout.pixel_to_axial(x + width / 2.0, center_y);
+    Ok(command::Create {
+        owner,
+        pos,
+        prototype: prototype.into(),
+        overrides: Vec::new(),
+    })
+}
+
+/// Loads a Tiled map from the content of a `.tmx` file.
+///
+/// The resulting map is centered around `[0, 0]`.
+pub fn load(data: &str, prototypes: &Prototypes) -> Result<Scenario, Error> {
+    let map = parse_xml(data)?;
+    let layout = Layout::from_map(&map)?;
+    let tiles = load_tiles(&map, &layout)?;
+    let width: i32 = map.attr_parse("width")?;
+    let height: i32 = map.attr_parse("height")?;
+    let center = layout.offset_to_axial(width / 2, height / 2);
+    let shape = tiles.shape();
+    let centered_shape = Shape::from_fn(shape.min() - center, shape.max() - center, |pos| {
+        shape.is_inboard(pos + center)
+    });
+    let mut scenario = Scenario::new(centered_shape);
+    for pos in tiles.iter() {
+        scenario.map.set_tile(pos - center, tiles.tile(pos));
+    }
+    for group in map.children("objectgroup") {
+        for object in group.children("object") {
+            let mut command = load_object(object, &layout, prototypes)?;
+            let id: u32 = object.attr_parse("id")?;
+            command.pos = command.pos - center;
+            if !scenario.map.is_inboard(command.pos) {
+                return Err(Error::ObjectIsOutsideTheMap(id));
+            }
+            if scenario.object_at(command.pos).is_some() {
+                return Err(Error::TileIsOccupied(id));
+            }
+            scenario.add_object(command);
+        }
+    }
+    Ok(scenario)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use core::map::PosHex;
+    use core::prototype::Prototype;
+    use core::{PlayerId, Prototypes, TileType};
+    use super::{load, Error, Layout, StaggerIndex};
+
+    const ATTRS: &str = r#"orientation="hexagonal" hexsidelength="16" staggerindex="odd""#;
+
+    const TILESET: &str = r#"
+        <tileset firstgid="1" name="terrain">
+            <tile id="0" type="plain"/>
+            <tile id="1">
+                <properties><property name="tile_type" value="rocks"/></properties>
+            </tile>
+        </tileset>"#;
+
+    const DATA: &str = "1,1,1,\n1,2,1,\n1,1,1";
+
+    /// Occupies the central tile
+    const OBJECT: &str = r#"
+        <object id="1" type="imp" x="64" y="36">
+            <properties><property name="owner" value="1"/></properties>
+        </object>"#;
+
+    fn map_xml(attrs: &str, tileset: &str, data: &str, objects: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <map {} width="3" height="3" tilewidth="32" tileheight="32">
+                {}
+                <layer name="ground" width="3" height="3">
+                    <data encoding="csv">{}</data>
+                </layer>
+                <objectgroup name="objects">{}</objectgroup>
+            </map>"#,
+            attrs, tileset, data, objects
+        )
+    }
+
+    fn prototypes() -> Prototypes {
+        let mut prototypes = HashMap::new();
+        prototypes.insert("imp".into(), Prototype::default());
+        Prototypes(prototypes)
+    }
+
+    fn load_error(data: &str) -> Error {
+        load(data, &prototypes()).unwrap_err()
+    }
+
+    fn layout(stagger_index: StaggerIndex) -> Layout {
+        Layout {
+            stagger_index,
+            tile_width: 32.0,
+            row_height: 24.0,
+        }
+    }
+
+    #[test]
+    fn offset_to_axial_odd() {
+        let layout = layout(StaggerIndex::Odd);
+        assert_eq!(layout.offset_to_axial(0, 0), PosHex { q: 0, r: 0 });
+        assert_eq!(layout.offset_to_axial(1, 0), PosHex { q: 1, r: 0 });
+        // odd rows are shifted right
+        assert_eq!(layout.offset_to_axial(0, 1), PosHex { q: 1, r: -1 });
+        assert_eq!(layout.offset_to_axial(0, 2), PosHex { q: 1, r: -2 });
+        assert_eq!(layout.offset_to_axial(2, 3), PosHex { q: 4, r: -3 });
+    }
+
+    #[test]
+    fn offset_to_axial_even() {
+        let layout = layout(StaggerIndex::Even);
+        assert_eq!(layout.offset_to_axial(0, 0), PosHex { q: 0, r: 0 });
+        assert_eq!(layout.offset_to_axial(1, 0), PosHex { q: 1, r: 0 });
+        // odd rows are shifted left
+        assert_eq!(layout.offset_to_axial(0, 1), PosHex { q: 0, r: -1 });
+        assert_eq!(layout.offset_to_axial(0, 2), PosHex { q: 1, r: -2 });
+        assert_eq!(layout.offset_to_axial(2, 3), PosHex { q: 3, r: -3 });
+    }
+
+    #[test]
+    fn pixel_to_axial() {
+        let layout = layout(StaggerIndex::Odd);
+        assert_eq!(layout.pixel_to_axial(16.0, 12.0), PosHex { q: 0, r: 0 });
+        assert_eq!(layout.pixel_to_axial(64.0, 36.0), PosHex { q: 2, r: -1 });
+    }
+
+    #[test]
+    fn load_map() {
+        let data = map_xml(ATTRS, TILESET, DATA, OBJECT);
+        let scenario = load(&data, &prototypes()).unwrap();
+        let center = PosHex { q: 0, r: 0 };
+        assert_eq!(scenario.map.shape().tiles_count(), 9);
+        assert_eq!(scenario.map.tile(center), TileType::Rocks);
+        assert_eq!(scenario.map.tile(PosHex { q: 1, r: 0 }), TileType::Plain);
+        let object = scenario.object_at(center).unwrap();
+        assert_eq!(object.prototype, "imp");
+        assert_eq!(object.owner, Some(PlayerId(1)));
+    }
+
+    #[test]
+    fn bad_xml() {
+        match load_error("<map") {
+            Error::Xml(_) => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn no_map_element() {
+        assert_eq!(load_error("<world/>"), Error::NoMapElement);
+    }
+
+    #[test]
+    fn missing_attribute() {
+        let data = map_xml(r#"orientation="hexagonal""#, TILESET, DATA, "");
+        let expected = Error::MissingAttribute {
+            element: "map".into(),
+            attribute: "hexsidelength".into(),
+        };
+        assert_eq!(load_error(&data), expected);
+    }
+
+    #[test]
+    fn bad_attribute() {
+        let attrs = r#"orientation="hexagonal" hexsidelength="16" staggerindex="third""#;
+        let data = map_xml(attrs, TILESET, DATA, "");
+        let expected = Error::BadAttribute {
+            attribute: "staggerindex".into(),
+            value: "third".into(),
+        };
+        assert_eq!(load_error(&data), expected);
+    }
+
+    #[test]
+    fn unsupported_orientation() {
+        let data = map_xml(r#"orientation="orthogonal""#, TILESET, DATA, "");
+        let expected = Error::UnsupportedOrientation("orthogonal".into());
+        assert_eq!(load_error(&data), expected);
+    }
+
+    #[test]
+    fn unsupported_stagger_axis() {
+        let attrs = r#"orientation="staggered" staggeraxis="x""#;
+        let data = map_xml(attrs, TILESET, DATA, "");
+        assert_eq!(load_error(&data), Error::UnsupportedStaggerAxis("x".into()));
+    }
+
+    #[test]
+    fn unsupported_encoding() {
+        let data = map_xml(ATTRS, TILESET, DATA, "").replace(r#" encoding="csv""#, "");
+        assert_eq!(load_error(&data), Error::UnsupportedEncoding("xml".into()));
+    }
+
+    #[test]
+    fn external_tileset() {
+        let tileset = r#"<tileset firstgid="1" source="terrain.tsx"/>"#;
+        let data = map_xml(ATTRS, tileset, DATA, "");
+        let expected = Error::ExternalTileset("terrain.tsx".into());
+        assert_eq!(load_error(&data), expected);
+    }
+
+    #[test]
+    fn empty_map() {
+        let data = map_xml(ATTRS, TILESET, "0,0,0,0,0,0,0,0,0", "");
+        assert_eq!(load_error(&data), Error::EmptyMap);
+    }
+
+    #[test]
+    fn unknown_tile_type() {
+        let tileset = r#"<tileset firstgid="1"><tile id="0" type="lava"/></tileset>"#;
+        let data = map_xml(ATTRS, tileset, DATA, "");
+        assert_eq!(load_error(&data), Error::UnknownTileType("lava".into()));
+    }
+
+    #[test]
+    fn unknown_tile() {
+        let data = map_xml(ATTRS, TILESET, "1,1,1,1,3,1,1,1,1", "");
+        assert_eq!(load_error(&data), Error::UnknownTile(3));
+    }
+
+    #[test]
+    fn unknown_prototype() {
+        let object = r#"<object id="1" type="dragon" x="64" y="36"/>"#;
+        let data = map_xml(ATTRS, TILESET, DATA, object);
+        assert_eq!(load_error(&data), Error::UnknownPrototype("dragon".into()));
+    }
+
+    #[test]
+    fn object_without_prototype() {
+        let object = r#"<object id="7" x="64" y="36"/>"#;
+        let data = map_xml(ATTRS, TILESET, DATA, object);
+        assert_eq!(load_error(&data), Error::ObjectWithoutPrototype(7));
+    }
+
+    #[test]
+    fn object_is_outside_the_map() {
+        let object = r#"<object id="7" type="imp" x="1000" y="36"/>"#;
+        let data = map_xml(ATTRS, TILESET, DATA, object);
+        assert_eq!(load_error(&data), Error::ObjectIsOutsideTheMap(7));
+    }
+
+    #[test]
+    fn tile_is_occupied() {
+        let objects = format!("{}{}", OBJECT, r#"<object id="2" type="imp" x="70" y="40"/>"#);
+        let data = map_xml(ATTRS, TILESET, DATA, &objects);
+        assert_eq!(load_error(&data), Error::TileIsOccupied(2));
+    }
+
+    #[test]
+    fn bad_owner() {
+        let data = map_xml(ATTRS, TILESET, DATA, OBJECT).replace(r#"value="1""#, r#"value="x""#);
+        assert_eq!(load_error(&data), Error::BadOwner("x".into()));
+    }
+}
@@ -1,15 +1,106 @@
 use std::collections::HashMap;
-use hate::{Context, Scene, Sprite, Time};
-use hate::scene::Layer;
+use rancor::Join;
+use cgmath::Vector2;
+use hate::{Context, Scene, Sound, Sprite, SpriteSheet, Time};
+use hate::geom::Point;
+use hate::scene::{Bounds, Camera, Layer};
 use hate::scene::action::{self, Action};
-use core::{check, Jokers, Moves, State};
+use core::{check, Height, Jokers, Moves, Prototypes, State, TileType};
 use core::ObjId;
-use core::map::HexMap;
-use core::movement::Tile;
+use core::map::{HexMap, PosHex, Shape};
+use core::movement::{Path, Tile};
 use core::command;
-use map::hex_to_point;
+use map::{self, hex_to_point};
 
 const WALKBALE_TILE_COLOR: [f32; 4] = [0.2, 1.0, 0.2, 0.5];
+const HIGHLIGHTED_TILE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.4];
+const PATH_PREVIEW_COLOR: [f32; 4] = [0.2, 0.2, 1.0, 0.6];
+
+/// The whole map fits the screen with the minimal zoom.
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 4.0;
+
+/// Zoom multiplier for one line of scrolling
+const ZOOM_STEP: f32 = 1.2;
+
+pub fn tile_texture_name(tile: TileType) -> &'static str {
+    match tile {
+        TileType::Plain => "tile.png",
+        TileType::Rocks => "tile_rocks.png",
+    }
+}
+
+/// Lower tiles are darker
+pub fn height_color(height: Height) -> [f32; 4] {
+    let k = (0.7 + height.0 as f32 * 0.15).min(1.0);
+    [k, k, k, 1.0]
+}
+
+/// Shown for objects whose prototypes have no known texture
+const DEFAULT_OBJECT_TEXTURE: &str = "white_hex.png";
+
+// TODO: Move to some .ron config
+fn known_object_texture_name(prototype: &str) -> Option<&'static str> {
+    match prototype {
+        "swordsman" => Some("swordsman.png"),
+        "spearman" => Some("spearman.png"),
+        "imp" => Some("imp.png"),
+        "boulder" => Some("boulder.png"),
+        _ => None,
+    }
+}
+
+/// Derived prototypes (like "elite_swordsman") use their parent's texture.
+///
+/// Returns `None` for prototypes without a texture in their lineage
+/// (abstract ones like "base_infantry").
+pub fn try_object_texture_name(prototypes: &Prototypes, prototype: &str) -> Option<&'static str> {
+    prototypes
+        .lineage(prototype)
+        .into_iter()
+        .filter_map(known_object_texture_name)
+        .next()
+}
+
+pub fn object_texture_name(prototypes: &Prototypes, prototype: &str) -> &'static str {
+    try_object_texture_name(prototypes, prototype).unwrap_or_else(|| {
+        warn!("No texture for prototype '{}'", prototype);
+        DEFAULT_OBJECT_TEXTURE
+    })
+}
+
+/// Finds the biggest tile size that allows the whole map to fit the screen.
+fn calc_tile_size(shape: &Shape, aspect_ratio: f32) -> f32 {
+    let margin = 1.5;
+    let mut max_x: f32 = 0.0;
+    let mut max_y: f32 = 0.0;
+    for pos in shape.iter() {
+        let point = hex_to_point(1.0, pos);
+        max_x = max_x.max(point.0.x.abs());
+        max_y = max_y.max(point.0.y.abs());
+    }
+    let size_y = 1.0 / (max_y + margin);
+    let size_x = aspect_ratio / (max_x + margin);
+    size_x.min(size_y)
+}
+
+/// The camera can't be moved away from the map.
+fn calc_camera_bounds(shape: &Shape, tile_size: f32) -> Bounds {
+    let mut max_x: f32 = 0.0;
+    let mut max_y: f32 = 0.0;
+    for pos in shape.iter() {
+        let point = hex_to_point(tile_size, pos);
+        max_x = max_x.max(point.0.x.abs());
+        max_y = max_y.max(point.0.y.abs());
+    }
+    Bounds {
+        min: Point(Vector2 {
+            x: -max_x,
+            y: -max_y,
+        }),
+        max: Point(Vector2 { x: max_x, y: max_y }),
+    }
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct Layers {
@@ -17,7 +108,9 @@ pub struct Layers {
     pub blood: Layer,
     pub grass: Layer,
     pub walkable_tiles: Layer,
+    pub path_preview: Layer,
     pub attackable_tiles: Layer,
+    pub highlighted_tile: Layer,
     pub selection_marker: Layer,
     pub units: Layer,
     pub text: Layer,
@@ -30,7 +123,9 @@ impl Layers {
             self.blood,
             self.grass,
             self.walkable_tiles,
+            self.path_preview,
             self.attackable_tiles,
+            self.highlighted_tile,
             self.selection_marker,
             self.units,
             self.text,
@@ -41,33 +136,71 @@ impl Layers {
 #[derive(Debug)]
 struct Sprites {
     selection_marker: Sprite,
+    highlighted_tile: Sprite,
     walkable_tiles: Vec<Sprite>,
+    path_preview: Vec<(PosHex, Sprite)>,
     attackable_tiles: Vec<Sprite>,
     id_to_sprite_map: HashMap<ObjId, Sprite>,
+    id_to_sprite_sheet_map: HashMap<ObjId, SpriteSheet>,
     unit_info: HashMap<ObjId, Vec<Sprite>>,
 }
 
+/// Optional: a missing sound file is just silent
+fn load_sound(context: &mut Context, path: &str) -> Option<Sound> {
+    let sound = Sound::try_from_path(context, path);
+    if sound.is_none() {
+        warn!("Can't load sound '{}'", path);
+    }
+    sound
+}
+
+#[derive(Debug)]
+pub struct Sounds {
+    pub attack: Option<Sound>,
+    pub miss: Option<Sound>,
+    pub kill: Option<Sound>,
+    pub turn: Option<Sound>,
+}
+
+impl Sounds {
+    fn new(context: &mut Context) -> Self {
+        Self {
+            attack: load_sound(context, "sounds/attack.ogg"),
+            miss: load_sound(context, "sounds/miss.ogg"),
+            kill: load_sound(context, "sounds/kill.ogg"),
+            turn: load_sound(context, "sounds/turn.ogg"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GameView {
     tile_size: f32,
     layers: Layers,
     scene: Scene,
     sprites: Sprites,
+    sounds: Sounds,
 }
 
 impl GameView {
-    pub fn new(state: &State, context: &mut Context) -> Self {
+    pub fn new(map_shape: &Shape, context: &mut Context) -> Self {
         let layers = Layers::default();
-        let scene = Scene::new(layers.clone().sorted());
-        let map_height = state.map().radius().0 * 2 + 1;
-        let tile_size = 1.0 / ((map_height + 1) as f32 * 0.75);
+        let mut scene = Scene::new(layers.clone().sorted());
+        let tile_size = calc_tile_size(map_shape, context.aspect_ratio());
+        let camera_bounds = calc_camera_bounds(map_shape, tile_size);
+        scene.camera_mut().set_bounds(Some(camera_bounds));
         let mut selection_marker = Sprite::from_path(context, "selection.png", tile_size * 2.0);
         selection_marker.set_color([0.0, 0.0, 1.0, 0.8]);
+        let mut highlighted_tile = Sprite::from_path(context, "tile.png", tile_size * 2.0);
+        highlighted_tile.set_color(HIGHLIGHTED_TILE_COLOR);
         let sprites = Sprites {
             selection_marker,
+            highlighted_tile,
             walkable_tiles: Vec::new(),
+            path_preview: Vec::new(),
             attackable_tiles: Vec::new(),
             id_to_sprite_map: HashMap::new(),
+            id_to_sprite_sheet_map: HashMap::new(),
             unit_info: HashMap::new(),
         };
         Self {
@@ -75,6 +208,7 @@ impl GameView {
             tile_size,
             layers,
             sprites,
+            sounds: Sounds::new(context),
         }
     }
 
@@ -87,6 +221,18 @@ impl GameView {
         self.scene.add_action(action);
     }
 
+    pub fn animation_speed(&self) -> f32 {
+        self.scene.speed()
+    }
+
+    pub fn set_animation_speed(&mut self, speed: f32) {
+        self.scene.set_speed(speed);
+    }
+
+    pub fn finish_all_actions(&mut self) {
+        self.scene.finish_all();
+    }
+
     pub fn tile_size(&self) -> f32 {
         self.tile_size
     }
@@ -95,18 +241,55 @@ impl GameView {
         &self.layers
     }
 
+    pub fn sounds(&self) -> &Sounds {
+        &self.sounds
+    }
+
+    pub fn camera(&self) -> &Camera {
+        self.scene.camera()
+    }
+
+    /// The tile under the point in the screen coordinates
+    pub fn point_to_hex(&self, point: Point) -> PosHex {
+        let world_point = self.camera().screen_to_world(point);
+        map::point_to_hex(self.tile_size, world_point)
+    }
+
+    /// Moves the map together with the dragged pointer.
+    pub fn pan(&mut self, screen_delta: Point) {
+        let camera = self.scene.camera_mut();
+        let pos = Point(camera.pos().0 - screen_delta.0 / camera.zoom());
+        camera.set_pos(pos);
+    }
+
+    /// Positive `lines` zoom in.
+    pub fn zoom(&mut self, lines: f32) {
+        let camera = self.scene.camera_mut();
+        let zoom = camera.zoom() * ZOOM_STEP.powf(lines);
+        camera.set_zoom(zoom.max(MIN_ZOOM).min(MAX_ZOOM));
+    }
+
     pub fn add_object(&mut self, id: ObjId, sprite: &Sprite) {
         self.sprites.id_to_sprite_map.insert(id, sprite.clone());
     }
 
     pub fn remove_object(&mut self, id: ObjId) {
         self.sprites.id_to_sprite_map.remove(&id).unwrap();
+        self.sprites.id_to_sprite_sheet_map.remove(&id);
     }
 
     pub fn id_to_sprite(&mut self, id: ObjId) -> &Sprite {
         &self.sprites.id_to_sprite_map[&id]
     }
 
+    pub fn set_sprite_sheet(&mut self, id: ObjId, sheet: SpriteSheet) {
+        self.sprites.id_to_sprite_sheet_map.insert(id, sheet);
+    }
+
+    pub fn sprite_sheet(&self, id: ObjId) -> Option<&SpriteSheet> {
+        self.sprites.id_to_sprite_sheet_map.get(&id)
+    }
+
     pub fn unit_info_check(&self, id: ObjId) -> bool {
         self.sprites.unit_info.get(&id).is_some()
     }
@@ -119,7 +302,57 @@ impl GameView {
         self.sprites.unit_info.insert(id, sprites);
     }
 
+    /// Highlights the tile under the pointer, `None` removes the highlighting.
+    pub fn highlight_tile(&mut self, pos: Option<PosHex>) {
+        let layer = &self.layers.highlighted_tile;
+        let sprite = &mut self.sprites.highlighted_tile;
+        let is_shown = layer.has_sprite(sprite);
+        match pos {
+            Some(pos) => {
+                sprite.set_pos(hex_to_point(self.tile_size, pos));
+                if !is_shown {
+                    self.scene.add_action(Box::new(action::Show::new(layer, sprite)));
+                }
+            }
+            None => if is_shown {
+                self.scene.add_action(Box::new(action::Hide::new(layer, sprite)));
+            },
+        }
+    }
+
+    /// Marks the tiles of the path the selected unit would walk.
+    pub fn show_path_preview(&mut self, context: &mut Context, path: &Path) {
+        // The unit's own tile isn't marked
+        let tiles = &path.tiles()[1..];
+        let is_same_path = self.sprites.path_preview.len() == tiles.len()
+            && self.sprites
+                .path_preview
+                .iter()
+                .zip(tiles)
+                .all(|(&(pos, _), &tile)| pos == tile);
+        if is_same_path {
+            return;
+        }
+        self.hide_path_preview();
+        for &pos in tiles {
+            let mut sprite = Sprite::from_path(context, "tile.png", self.tile_size());
+            sprite.set_color(PATH_PREVIEW_COLOR);
+            sprite.set_pos(hex_to_point(self.tile_size(), pos));
+            let action = Box::new(action::Show::new(&self.layers().path_preview, &sprite));
+            self.add_action(action);
+            self.sprites.path_preview.push((pos, sprite));
+        }
+    }
+
+    pub fn hide_path_preview(&mut self) {
+        for (_, sprite) in self.sprites.path_preview.split_off(0) {
+            let action = Box::new(action::Hide::new(&self.layers().path_preview, &sprite));
+            self.add_action(action);
+        }
+    }
+
     pub fn deselect(&mut self) {
+        self.hide_path_preview();
         let action_hide = Box::new(action::Hide::new(
             &self.layers.selection_marker,
             &self.sprites.selection_marker,
@@ -172,11 +405,12 @@ impl GameView {
     fn show_attackable_tiles(&mut self, state: &State, context: &mut Context, id: ObjId) {
         let parts = state.parts();
         let selected_unit_player_id = parts.belongs_to.get(id).0;
-        for target_id in parts.agent.ids() {
-            let target_pos = parts.pos.get(target_id).0;
-            let target_player_id = parts.belongs_to.get(target_id).0;
-            if target_player_id == selected_unit_player_id {
-                continue;
+        for (target_id, _, target_pos) in (&parts.strength, &parts.pos).join() {
+            let target_pos = target_pos.0;
+            if let Some(belongs_to) = parts.belongs_to.get_opt(target_id) {
+                if belongs_to.0 == selected_unit_player_id {
+                    continue;
+                }
             }
             let command_attack = command::Command::Attack(command::Attack {
                 attacker_id: id,
@@ -12,6 +12,8 @@ extern crate env_logger;
 extern crate hate;
 extern crate rand;
 extern crate ron;
+extern crate serde;
+extern crate xml;
 
 mod screen;
 mod map;
@@ -23,6 +25,11 @@ mod ai;
 pub fn main() {
     env_logger::init().expect("Can't initialize logging");
     enable_backtrace();
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 1 && args[1] == "--mapgen" {
+        print_generated_map(&args[2..]);
+        return;
+    }
     let settings = ron::de::from_str(&hate::fs::load_as_string("settings.ron")).unwrap();
     let mut visualizer = hate::Visualizer::new(settings);
     let start_screen = Box::new(screen::MainMenu::new(visualizer.context_mut()));
@@ -34,3 +41,38 @@ fn enable_backtrace() {
         std::env::set_var("RUST_BACKTRACE", "1");
     }
 }
+
+/// `hexagon`, `parallelogram`, `rectangle` or `island`
+fn map_shape(name: &str) -> core::map::Shape {
+    use core::map::{self, Distance, PosHex, Shape};
+    match name {
+        "hexagon" => Shape::hexagon(Distance(5)),
+        "parallelogram" => Shape::parallelogram(11, 11),
+        "rectangle" => Shape::rectangle(15, 11),
+        "island" => {
+            // A rectangle with cut corners
+            let origin = PosHex { q: 0, r: 0 };
+            Shape::rectangle(15, 11).filter(|pos| map::distance_hex(origin, pos) <= Distance(6))
+        }
+        _ => panic!("Unknown map shape: '{}'", name),
+    }
+}
+
+/// Prints an ASCII preview of a generated map:
+/// `zemeroth --mapgen [seed] [chokepoints] [shape]`
+fn print_generated_map(args: &[String]) {
+    let parse = |i: usize| -> Option<i32> {
+        args.get(i).map(|s| s.parse().expect("Can't parse the argument"))
+    };
+    let mut options = core::mapgen::Options::default();
+    if let Some(seed) = parse(0) {
+        options.seed = seed as u32;
+    }
+    if let Some(chokepoints) = parse(1) {
+        options.chokepoints = chokepoints;
+    }
+    let shape = map_shape(args.get(2).map(|s| s.as_str()).unwrap_or("hexagon"));
+    let generated_map = core::mapgen::generate(&shape, &options);
+    println!("{:?}", options);
+    print!("{}", generated_map);
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use ron;
+use ron::ser::PrettyConfig;
+use hate::{self, Context, Event, Screen, Sprite, SpriteSheet, Time};
+use hate::geom::Point;
+use hate::gui::{self, Gui};
+use hate::scene::action;
+use core::{Height, PlayerId, Prototypes, TileType};
+use core::command;
+use core::map::{self as hex, Distance, PosHex, Shape};
+use core::prototype;
+use core::scenario::Scenario;
+use core::tiled;
+use game_view::{self, GameView};
+use map;
+
+const SCENARIO_PATH: &str = "scenario.ron";
+const TILED_MAP_PATH: &str = "map.tmx";
+
+#[derive(Clone, Debug)]
+enum Tool {
+    Tile(TileType),
+
+    /// Changes the tile's height by the given number of levels
+    Height(i32),
+
+    Object(String),
+    SpawnZone,
+    Eraser,
+}
+
+/// Tiles affected by the tile and height tools
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Brush {
+    /// All tiles within the radius
+    Radius(Distance),
+
+    /// A line from the previously clicked tile
+    Line,
+
+    /// The connected area of tiles with the same type and height
+    Fill,
+}
+
+impl Brush {
+    fn next(self) -> Brush {
+        match self {
+            Brush::Radius(Distance(0)) => Brush::Radius(Distance(1)),
+            Brush::Radius(_) => Brush::Line,
+            Brush::Line => Brush::Fill,
+            Brush::Fill => Brush::Radius(Distance(0)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum GuiCommand {
+    Exit,
+    Save,
+    Load,
+    Import,
+    NextOwner,
+    NextBrush,
+    SelectTool(Tool),
+}
+
+fn spawn_zone_color(player_id: PlayerId) -> [f32; 4] {
+    match player_id.0 {
+        0 => [0.0, 0.0, 1.0, 0.4],
+        1 => [1.0, 0.0, 0.0, 0.4],
+        _ => [0.5, 0.5, 0.5, 0.4],
+    }
+}
+
+fn owner_label(owner: Option<PlayerId>) -> String {
+    match owner {
+        Some(player_id) => format!("owner: {}", player_id.0),
+        None => "owner: none".into(),
+    }
+}
+
+fn brush_label(brush: Brush) -> String {
+    match brush {
+        Brush::Radius(radius) => format!("brush: radius {}", radius.0),
+        Brush::Line => "brush: line".into(),
+        Brush::Fill => "brush: fill".into(),
+    }
+}
+
+fn tool_label(tool: &Tool) -> String {
+    match *tool {
+        Tool::Tile(TileType::Plain) => "plain".into(),
+        Tool::Tile(TileType::Rocks) => "rocks".into(),
+        Tool::Height(delta) if delta > 0 => "raise".into(),
+        Tool::Height(_) => "lower".into(),
+        Tool::Object(ref prototype) => prototype.clone(),
+        Tool::SpawnZone => "spawn zone".into(),
+        Tool::Eraser => "eraser".into(),
+    }
+}
+
+fn build_gui(
+    context: &mut Context,
+    prototype_names: &[String],
+    brush: Brush,
+) -> (Gui<GuiCommand>, gui::Id, gui::Id) {
+    let mut gui = Gui::new(context);
+    let direction = gui::Direction::Up;
+    let mut button = |gui: &mut Gui<GuiCommand>, label: &str, command: GuiCommand| {
+        let sprite = gui::text_sprite(context, label, 0.08);
+        gui.add_button(context, sprite, command)
+    };
+    {
+        let ids = vec![
+            button(&mut gui, "exit", GuiCommand::Exit),
+            button(&mut gui, "import", GuiCommand::Import),
+            button(&mut gui, "load", GuiCommand::Load),
+            button(&mut gui, "save", GuiCommand::Save),
+        ];
+        let anchor = gui::Anchor {
+            vertical: gui::VAnchor::Top,
+            horizontal: gui::HAnchor::Left,
+        };
+        gui.add_layout(anchor, direction, ids);
+    }
+    {
+        let mut tools = vec![
+            Tool::Eraser,
+            Tool::SpawnZone,
+            Tool::Height(-1),
+            Tool::Height(1),
+            Tool::Tile(TileType::Rocks),
+            Tool::Tile(TileType::Plain),
+        ];
+        for name in prototype_names.iter().rev() {
+            tools.push(Tool::Object(name.clone()));
+        }
+        let ids = tools
+            .into_iter()
+            .map(|tool| {
+                let label = tool_label(&tool);
+                button(&mut gui, &label, GuiCommand::SelectTool(tool))
+            })
+            .collect();
+        let anchor = gui::Anchor {
+            vertical: gui::VAnchor::Bottom,
+            horizontal: gui::HAnchor::Right,
+        };
+        gui.add_layout(anchor, direction, ids);
+    }
+    let owner_button_id = button(&mut gui, &owner_label(None), GuiCommand::NextOwner);
+    let brush_button_id = button(&mut gui, &brush_label(brush), GuiCommand::NextBrush);
+    {
+        let anchor = gui::Anchor {
+            vertical: gui::VAnchor::Bottom,
+            horizontal: gui::HAnchor::Left,
+        };
+        gui.add_layout(anchor, direction, vec![owner_button_id, brush_button_id]);
+    }
+    (gui, owner_button_id, brush_button_id)
+}
+
+#[derive(Debug)]
+pub struct Editor {
+    gui: Gui<GuiCommand>,
+    owner_button_id: gui::Id,
+    brush_button_id: gui::Id,
+    prototypes: Prototypes,
+    scenario: Scenario,
+    view: GameView,
+    tool: Tool,
+    owner: Option<PlayerId>,
+    brush: Brush,
+    last_clicked_pos: Option<PosHex>,
+    tile_sprites: HashMap<PosHex, Sprite>,
+    object_sprites: HashMap<PosHex, Sprite>,
+    spawn_zone_sprites: HashMap<(PlayerId, PosHex), Sprite>,
+}
+
+impl Editor {
+    pub fn new(context: &mut Context) -> Self {
+        let prototypes_str = hate::fs::load_as_string("objects.ron");
+        let prototypes = match prototype::load(&prototypes_str) {
+            Ok(prototypes) => prototypes,
+            Err(err) => panic!("Bad objects.ron: {}", err),
+        };
+        // Abstract prototypes can't be placed on the map
+        let mut prototype_names: Vec<String> = prototypes
+            .0
+            .keys()
+            .filter(|name| game_view::try_object_texture_name(&prototypes, name).is_some())
+            .cloned()
+            .collect();
+        prototype_names.sort();
+        let brush = Brush::Radius(Distance(0));
+        let (gui, owner_button_id, brush_button_id) = build_gui(context, &prototype_names, brush);
+        let scenario = Scenario::new(Shape::hexagon(Distance(5)));
+        let view = GameView::new(scenario.map.shape(), context);
+        let mut editor = Self {
+            gui,
+            owner_button_id,
+            brush_button_id,
+            prototypes,
+            scenario,
+            view,
+            tool: Tool::Tile(TileType::Rocks),
+            owner: None,
+            brush,
+            last_clicked_pos: None,
+            tile_sprites: HashMap::new(),
+            object_sprites: HashMap::new(),
+            spawn_zone_sprites: HashMap::new(),
+        };
+        editor.rebuild_view(context);
+        editor
+    }
+
+    /// Recreates all the sprites from scratch (e.g. after the map's shape was changed)
+    fn rebuild_view(&mut self, context: &mut Context) {
+        self.view = GameView::new(self.scenario.map.shape(), context);
+        self.tile_sprites.clear();
+        self.object_sprites.clear();
+        self.spawn_zone_sprites.clear();
+        let shape = self.scenario.map.shape().clone();
+        for pos in shape.iter() {
+            self.show_tile(context, pos);
+        }
+        for object in self.scenario.objects.clone() {
+            self.show_object(context, &object);
+        }
+        for zone in self.scenario.spawn_zones.clone() {
+            for &pos in &zone.tiles {
+                self.show_spawn_tile(context, zone.player_id, pos);
+            }
+        }
+    }
+
+    fn make_sprite(&self, context: &mut Context, texture_name: &str, pos: PosHex) -> Sprite {
+        let size = self.view.tile_size() * 2.0;
+        // Sprite sheets show their first frame
+        let sheet = SpriteSheet::from_path(context, texture_name, size);
+        let mut sprite = Sprite::from_sprite_sheet(&sheet);
+        sprite.set_pos(map::hex_to_point(self.view.tile_size(), pos));
+        sprite
+    }
+
+    fn show_tile(&mut self, context: &mut Context, pos: PosHex) {
+        if let Some(sprite) = self.tile_sprites.remove(&pos) {
+            let action = Box::new(action::Hide::new(&self.view.layers().bg, &sprite));
+            self.view.add_action(action);
+        }
+        let texture_name = game_view::tile_texture_name(self.scenario.map.tile(pos));
+        let mut sprite = self.make_sprite(context, texture_name, pos);
+        sprite.set_color(game_view::height_color(self.scenario.heights.tile(pos)));
+        let action = Box::new(action::Show::new(&self.view.layers().bg, &sprite));
+        self.view.add_action(action);
+        self.tile_sprites.insert(pos, sprite);
+    }
+
+    fn show_object(&mut self, context: &mut Context, object: &command::Create) {
+        let texture_name = game_view::object_texture_name(&self.prototypes, &object.prototype);
+        let sprite = self.make_sprite(context, texture_name, object.pos);
+        let action = Box::new(action::Show::new(&self.view.layers().units, &sprite));
+        self.view.add_action(action);
+        self.object_sprites.insert(object.pos, sprite);
+    }
+
+    fn hide_object(&mut self, pos: PosHex) {
+        if let Some(sprite) = self.object_sprites.remove(&pos) {
+            let action = Box::new(action::Hide::new(&self.view.layers().units, &sprite));
+            self.view.add_action(action);
+        }
+    }
+
+    fn show_spawn_tile(&mut self, context: &mut Context, player_id: PlayerId, pos: PosHex) {
+        let mut sprite = self.make_sprite(context, "white_hex.png", pos);
+        sprite.set_color(spawn_zone_color(player_id));
+        let action = {
+            let layer = &self.view.layers().walkable_tiles;
+            Box::new(action::Show::new(layer, &sprite))
+        };
+        self.view.add_action(action);
+        self.spawn_zone_sprites.insert((player_id, pos), sprite);
+    }
+
+    fn hide_spawn_tile(&mut self, player_id: PlayerId, pos: PosHex) {
+        if let Some(sprite) = self.spawn_zone_sprites.remove(&(player_id, pos)) {
+            let action = {
+                let layer = &self.view.layers().walkable_tiles;
+                Box::new(action::Hide::new(layer, &sprite))
+            };
+            self.view.add_action(action);
+        }
+    }
+
+    fn save(&self) {
+        let s = match ron::ser::to_string_pretty(&self.scenario, PrettyConfig::default()) {
+            Ok(s) => s,
+            Err(err) => {
+                error!("Editor: can't serialize the scenario: {:?}", err);
+                return;
+            }
+        };
+        match hate::fs::save(SCENARIO_PATH, s.as_bytes()) {
+            Ok(()) => info!("Editor: saved '{}'", SCENARIO_PATH),
+            Err(err) => error!("Editor: can't save '{}': {}", SCENARIO_PATH, err),
+        }
+    }
+
+    fn load(&mut self, context: &mut Context) {
+        let data = match hate::fs::try_load(SCENARIO_PATH) {
+            Some(data) => data,
+            None => {
+                error!("Editor: can't load '{}'", SCENARIO_PATH);
+                return;
+            }
+        };
+        let s = match String::from_utf8(data) {
+            Ok(s) => s,
+            Err(err) => {
+                error!("Editor: can't decode '{}': {}", SCENARIO_PATH, err);
+                return;
+            }
+        };
+        let scenario: Scenario = match ron::de::from_str(&s) {
+            Ok(scenario) => scenario,
+            Err(err) => {
+                error!("Editor: can't parse '{}': {:?}", SCENARIO_PATH, err);
+                return;
+            }
+        };
+        if let Err(err) = scenario.validate() {
+            error!("Editor: bad scenario '{}': {}", SCENARIO_PATH, err);
+            return;
+        }
+        self.scenario = scenario;
+        self.rebuild_view(context);
+    }
+
+    fn import(&mut self, context: &mut Context) {
+        let data = match hate::fs::try_load(TILED_MAP_PATH) {
+            Some(data) => data,
+            None => {
+                error!("Editor: can't load '{}'", TILED_MAP_PATH);
+                return;
+            }
+        };
+        let s = match String::from_utf8(data) {
+            Ok(s) => s,
+            Err(err) => {
+                error!("Editor: can't decode '{}': {}", TILED_MAP_PATH, err);
+                return;
+            }
+        };
+        match tiled::load(&s, &self.prototypes) {
+            Ok(scenario) => {
+                self.scenario = scenario;
+                self.rebuild_view(context);
+            }
+            Err(err) => error!("Editor: can't import '{}': {}", TILED_MAP_PATH, err),
+        }
+    }
+
+    fn next_owner(&mut self, context: &mut Context) {
+        self.owner = match self.owner {
+            None => Some(PlayerId(0)),
+            Some(PlayerId(0)) => Some(PlayerId(1)),
+            Some(_) => None,
+        };
+        let sprite = gui::text_sprite(context, &owner_label(self.owner), 0.08);
+        self.gui.update_sprite(context, self.owner_button_id, sprite);
+    }
+
+    fn next_brush(&mut self, context: &mut Context) {
+        self.brush = self.brush.next();
+        let sprite = gui::text_sprite(context, &brush_label(self.brush), 0.08);
+        self.gui.update_sprite(context, self.brush_button_id, sprite);
+    }
+
+    fn brush_tiles(&self, pos: PosHex) -> Vec<PosHex> {
+        let map = &self.scenario.map;
+        let heights = &self.scenario.heights;
+        let tiles = match self.brush {
+            Brush::Radius(radius) => hex::spiral(pos, radius),
+            Brush::Line => match self.last_clicked_pos {
+                Some(from) => hex::line(from, pos),
+                None => vec![pos],
+            },
+            Brush::Fill => {
+                let (tile, height) = (map.tile(pos), heights.tile(pos));
+                let max_steps = Distance(map.shape().tiles_count() as i32);
+                hex::reachable(pos, max_steps, |pos| {
+                    !map.is_inboard(pos) || map.tile(pos) != tile || heights.tile(pos) != height
+                })
+            }
+        };
+        tiles.into_iter().filter(|&pos| map.is_inboard(pos)).collect()
+    }
+
+    fn handle_command(&mut self, context: &mut Context, command: GuiCommand) {
+        match command {
+            GuiCommand::Exit => context.add_command(hate::screen::Command::Pop),
+            GuiCommand::Save => self.save(),
+            GuiCommand::Load => self.load(context),
+            GuiCommand::Import => self.import(context),
+            GuiCommand::NextOwner => self.next_owner(context),
+            GuiCommand::NextBrush => self.next_brush(context),
+            GuiCommand::SelectTool(tool) => self.tool = tool,
+        }
+    }
+
+    fn apply_tool(&mut self, context: &mut Context, pos: PosHex) {
+        match self.tool.clone() {
+            Tool::Tile(tile) => for pos in self.brush_tiles(pos) {
+                self.scenario.map.set_tile(pos, tile);
+                self.show_tile(context, pos);
+            },
+            Tool::Height(delta) => for pos in self.brush_tiles(pos) {
+                let height = (self.scenario.heights.tile(pos).0 + delta).max(0);
+                self.scenario.heights.set_tile(pos, Height(height));
+                self.show_tile(context, pos);
+            },
+            Tool::Object(prototype) => {
+                if self.scenario.object_at(pos).is_some() {
+                    return;
+                }
+                let object = command::Create {
+                    owner: self.owner,
+                    pos,
+                    prototype,
+                    overrides: Vec::new(),
+                };
+                self.show_object(context, &object);
+                self.scenario.add_object(object);
+            }
+            Tool::SpawnZone => {
+                let player_id = match self.owner {
+                    Some(player_id) => player_id,
+                    None => return,
+                };
+                if self.scenario.is_spawn_tile(player_id, pos) {
+                    self.hide_spawn_tile(player_id, pos);
+                } else {
+                    self.show_spawn_tile(context, player_id, pos);
+                }
+                self.scenario.toggle_spawn_tile(player_id, pos);
+            }
+            Tool::Eraser => {
+                self.scenario.remove_object_at(pos);
+                self.hide_object(pos);
+            }
+        }
+    }
+
+    fn handle_event_click(&mut self, context: &mut Context, point: Point) {
+        self.gui.click(point);
+        let mut is_gui_clicked = false;
+        while let Some(command) = self.gui.try_recv() {
+            self.handle_command(context, command);
+            is_gui_clicked = true;
+        }
+        if is_gui_clicked {
+            return;
+        }
+        let pos = self.view.point_to_hex(point);
+        if self.scenario.map.is_inboard(pos) {
+            self.apply_tool(context, pos);
+            self.last_clicked_pos = Some(pos);
+        }
+    }
+}
+
+impl Screen for Editor {
+    fn tick(&mut self, context: &mut Context, dtime: Time) {
+        self.view.tick(context, dtime);
+        self.gui.draw(context);
+    }
+
+    fn handle_event(&mut self, context: &mut Context, event: Event) {
+        match event {
+            Event::Click { pos } => self.handle_event_click(context, pos),
+            Event::Resize { aspect_ratio } => self.gui.resize(aspect_ratio),
+            Event::Drag { delta, .. } => self.view.pan(delta),
+            Event::Scroll { delta } => self.view.zoom(delta),
+            _ => {}
+        }
+    }
+}
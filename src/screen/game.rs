@@ -1,19 +1,20 @@
-use ron;
 use rand::{thread_rng, Rng};
 use cgmath::Vector2;
-use hate::{self, Context, Event, Screen, Sprite, Time};
+use hate::{self, Context, Event, Key, Screen, Settings, Sprite, Time};
 use hate::geom::Point;
 use hate::gui::{self, Gui};
 use hate::scene::action::{self, Action};
 use visualize;
 use map;
-use game_view::GameView;
+use game_view::{self, GameView};
 use ai::Ai;
-use core::{self, check, ObjId, PlayerId, State, TileType};
+use core::{self, check, Attacks, Jokers, Moves, ObjId, PlayerId, State};
 use core::command;
 use core::execute;
+use core::mapgen;
 use core::map::PosHex;
 use core::movement::Pathfinder;
+use core::prototype;
 
 #[derive(Copy, Clone, Debug)]
 enum GuiCommand {
@@ -22,6 +23,62 @@ enum GuiCommand {
     EndTurn,
 }
 
+/// Keyboard shortcuts, configured with `key_bindings` in `settings.ron`
+#[derive(Copy, Clone, Debug)]
+enum Hotkey {
+    EndTurn,
+    Deselect,
+    NextUnit,
+    Exit,
+    SpeedUp,
+    Skip,
+}
+
+const HOTKEYS: &[Hotkey] = &[
+    Hotkey::EndTurn,
+    Hotkey::Deselect,
+    Hotkey::NextUnit,
+    Hotkey::Exit,
+    Hotkey::SpeedUp,
+    Hotkey::Skip,
+];
+
+/// Animation speed while `Hotkey::SpeedUp` is toggled on
+const FAST_ANIMATION_SPEED: f32 = 3.0;
+
+impl Hotkey {
+    fn name(self) -> &'static str {
+        match self {
+            Hotkey::EndTurn => "end_turn",
+            Hotkey::Deselect => "deselect",
+            Hotkey::NextUnit => "next_unit",
+            Hotkey::Exit => "exit",
+            Hotkey::SpeedUp => "speed_up",
+            Hotkey::Skip => "skip",
+        }
+    }
+
+    fn default_keys(self) -> &'static [Key] {
+        match self {
+            Hotkey::EndTurn => &[Key::Enter, Key::E],
+            Hotkey::Deselect => &[Key::Escape],
+            Hotkey::NextUnit => &[Key::Tab],
+            Hotkey::Exit => &[Key::Q],
+            Hotkey::SpeedUp => &[Key::F],
+            Hotkey::Skip => &[Key::Space],
+        }
+    }
+
+    fn from_key(settings: &Settings, key: Key) -> Option<Hotkey> {
+        HOTKEYS.iter().cloned().find(|hotkey| {
+            let keys = settings
+                .keys(hotkey.name())
+                .unwrap_or_else(|| hotkey.default_keys());
+            keys.contains(&key)
+        })
+    }
+}
+
 fn make_action_show_tile(
     context: &mut Context,
     state: &State,
@@ -29,13 +86,11 @@ fn make_action_show_tile(
     at: PosHex,
 ) -> Box<Action> {
     let screen_pos = map::hex_to_point(view.tile_size(), at);
-    let texture_name = match state.map().tile(at) {
-        TileType::Plain => "tile.png",
-        TileType::Rocks => "tile_rocks.png",
-    };
+    let texture_name = game_view::tile_texture_name(state.map().tile(at));
     let size = view.tile_size() * 2.0;
     let mut sprite = Sprite::from_path(context, texture_name, size);
     sprite.set_pos(screen_pos);
+    sprite.set_color(game_view::height_color(state.heights().tile(at)));
     Box::new(action::Show::new(&view.layers().bg, &sprite))
 }
 
@@ -136,9 +191,13 @@ fn build_gui(context: &mut Context) -> Gui<GuiCommand> {
 
 fn prepare_map_and_state(context: &mut Context, state: &mut State, view: &mut GameView) {
     let mut actions = Vec::new();
-    execute::create_terrain(state);
+    let mapgen_options = mapgen::Options {
+        seed: thread_rng().gen(),
+        ..Default::default()
+    };
+    let obstacles = execute::create_terrain(state, &mapgen_options);
     actions.push(make_action_create_map(state, view, context));
-    execute::create_objects(state, &mut |state, event, phase| {
+    execute::create_objects(state, &obstacles, &mut |state, event, phase| {
         let action = visualize::visualize(state, view, context, event, phase);
         let action = Box::new(action::Fork::new(action));
         actions.push(action);
@@ -161,20 +220,23 @@ pub struct Game {
 impl Game {
     pub fn new(context: &mut Context) -> Self {
         let prototypes_str = hate::fs::load_as_string("objects.ron");
-        let prototypes = ron::de::from_str(&prototypes_str).unwrap();
+        let prototypes = match prototype::load(&prototypes_str) {
+            Ok(prototypes) => prototypes,
+            Err(err) => panic!("Bad objects.ron: {}", err),
+        };
         debug!("{:?}", prototypes);
         let mut state = State::new(prototypes);
-        let radius = state.map().radius();
-        let mut view = GameView::new(&state, context);
+        let mut view = GameView::new(state.map().shape(), context);
         prepare_map_and_state(context, &mut state, &mut view);
+        let map_shape = state.map().shape().clone();
         Self {
             gui: build_gui(context),
             state,
             view,
             selected_unit_id: None,
-            pathfinder: Pathfinder::new(radius),
+            pathfinder: Pathfinder::new(&map_shape),
             block_timer: None,
-            ai: Ai::new(PlayerId(1), radius),
+            ai: Ai::new(PlayerId(1), &map_shape),
             layout_id_info: None,
         }
     }
@@ -211,6 +273,103 @@ impl Game {
         Box::new(action::Sequence::new(actions))
     }
 
+    fn handle_event_pointer_move(&mut self, context: &mut Context, point: Point) {
+        let pos = self.view.point_to_hex(point);
+        if self.state.map().is_inboard(pos) {
+            self.view.highlight_tile(Some(pos));
+            self.update_path_preview(context, pos);
+        } else {
+            self.view.highlight_tile(None);
+            self.view.hide_path_preview();
+        }
+    }
+
+    /// Shows the path to the tile under the pointer
+    /// if the selected unit can walk there right now.
+    fn update_path_preview(&mut self, context: &mut Context, pos: PosHex) {
+        let id = match self.selected_unit_id {
+            Some(id) => id,
+            None => return,
+        };
+        let path = match self.pathfinder.path(pos) {
+            Some(path) => path,
+            None => {
+                self.view.hide_path_preview();
+                return;
+            }
+        };
+        let command_move = command::Command::MoveTo(command::MoveTo {
+            id,
+            path: path.clone(),
+        });
+        if check(&self.state, &command_move).is_ok() {
+            self.view.show_path_preview(context, &path);
+        } else {
+            self.view.hide_path_preview();
+        }
+    }
+
+    fn handle_event_key_down(&mut self, context: &mut Context, key: Key) {
+        match Hotkey::from_key(context.settings(), key) {
+            Some(Hotkey::EndTurn) => self.end_turn(context),
+            Some(Hotkey::Deselect) => self.deselect(),
+            Some(Hotkey::NextUnit) => self.select_next_unit(context),
+            Some(Hotkey::Exit) => self.exit(context),
+            Some(Hotkey::SpeedUp) => self.toggle_animation_speed(),
+            Some(Hotkey::Skip) => self.skip_animations(),
+            None => {}
+        }
+    }
+
+    fn toggle_animation_speed(&mut self) {
+        let speed = if self.view.animation_speed() > 1.0 {
+            1.0
+        } else {
+            FAST_ANIMATION_SPEED
+        };
+        self.view.set_animation_speed(speed);
+    }
+
+    /// Brings all running animations to their end states
+    fn skip_animations(&mut self) {
+        self.view.finish_all_actions();
+        if self.block_timer.is_some() {
+            // Let `update_block_timer` do the unblocking
+            self.block_timer = Some(Time(0.0));
+        }
+    }
+
+    /// Selects the player's next unit that still can move or attack.
+    fn select_next_unit(&mut self, context: &mut Context) {
+        if self.block_timer.is_some() {
+            return;
+        }
+        let mut ids: Vec<ObjId> = {
+            let state = &self.state;
+            let agents = &state.parts().agent;
+            core::players_agent_ids(state, state.player_id())
+                .into_iter()
+                .filter(|&id| {
+                    let agent = agents.get(id);
+                    agent.moves > Moves(0) || agent.attacks > Attacks(0)
+                        || agent.jokers > Jokers(0)
+                })
+                .collect()
+        };
+        if ids.is_empty() {
+            return;
+        }
+        ids.sort();
+        let next_id = match self.selected_unit_id {
+            Some(selected_id) => ids.iter()
+                .cloned()
+                .find(|&id| id > selected_id)
+                .unwrap_or(ids[0]),
+            None => ids[0],
+        };
+        self.select_unit(context, next_id);
+    }
+
     fn handle_commands(&mut self, context: &mut Context) {
         while let Some(command) = self.gui.try_recv() {
             match command {
@@ -239,6 +398,12 @@ impl Game {
     fn do_command(&mut self, context: &mut Context, command: &command::Command) {
         let action = self.do_command_inner(context, command);
         self.add_action(action);
+        // The selected unit may have been killed by a reaction attack
+        if let Some(id) = self.selected_unit_id {
+            if !self.state.parts().is_alive(id) {
+                self.deselect();
+            }
+        }
     }
 
     fn add_actions(&mut self, actions: Vec<Box<Action>>) {
@@ -275,68 +440,74 @@ impl Game {
     }
 
     fn handle_event_click(&mut self, context: &mut Context, point: Point) {
-        let pos = map::point_to_hex(self.view.tile_size(), point);
+        let pos = self.view.point_to_hex(point);
         self.gui.click(point);
         if self.block_timer.is_some() {
             return;
         }
-        if self.state.map().is_inboard(pos) {
-            let object_ids = core::object_ids_at(&self.state, pos);
-            debug!("object_ids: {:?}", object_ids);
-            if !object_ids.is_empty() {
-                assert_eq!(object_ids.len(), 1);
-                let id = object_ids[0];
-                if self.state.parts().agent.get_opt(id).is_none() {
-                    // only agents can be selected
+        if !self.state.map().is_inboard(pos) {
+            return;
+        }
+        for id in core::object_ids_at(&self.state, pos) {
+            debug!("{:?}: {:?}", id, self.state.parts().components_of(id));
+        }
+        if let Some(id) = core::agent_id_at(&self.state, pos) {
+            let other_unit_player_id = self.state.parts().belongs_to.get(id).0;
+            if let Some(selected_unit_id) = self.selected_unit_id {
+                let selected_unit_player_id =
+                    self.state.parts().belongs_to.get(selected_unit_id).0;
+                if selected_unit_id == id {
+                    self.deselect();
                     return;
                 }
-                let other_unit_player_id = self.state.parts().belongs_to.get(id).0;
-                if let Some(selected_unit_id) = self.selected_unit_id {
-                    let selected_unit_player_id =
-                        self.state.parts().belongs_to.get(selected_unit_id).0;
-                    if selected_unit_id == id {
-                        self.deselect();
-                        return;
-                    }
-                    if other_unit_player_id == selected_unit_player_id
-                        || other_unit_player_id == self.state.player_id()
-                    {
-                        self.select_unit(context, id);
-                        return;
-                    }
-                    let command_attack = command::Command::Attack(command::Attack {
-                        attacker_id: selected_unit_id,
-                        target_id: id,
-                    });
-                    if check(&self.state, &command_attack).is_err() {
-                        return;
-                    }
-                    self.do_command(context, &command_attack);
-                    let parts = self.state.parts();
-                    if parts.agent.get_opt(selected_unit_id).is_some() {
-                        self.pathfinder.fill_map(&self.state, selected_unit_id);
-                    }
-                } else {
+                if other_unit_player_id == selected_unit_player_id
+                    || other_unit_player_id == self.state.player_id()
+                {
                     self.select_unit(context, id);
-                }
-            } else if let Some(id) = self.selected_unit_id {
-                let path = match self.pathfinder.path(pos) {
-                    Some(path) => path,
-                    None => return,
-                };
-                let command_move = command::Command::MoveTo(command::MoveTo { id, path });
-                if check(&self.state, &command_move).is_err() {
                     return;
                 }
-                self.do_command(context, &command_move);
-                if self.state.parts().agent.get_opt(id).is_some() {
-                    self.pathfinder.fill_map(&self.state, id);
-                }
+                self.attack(context, selected_unit_id, id);
+            } else {
+                self.select_unit(context, id);
             }
+        } else if let Some(id) = core::destructible_id_at(&self.state, pos) {
+            if let Some(selected_unit_id) = self.selected_unit_id {
+                self.attack(context, selected_unit_id, id);
+            }
+        } else if let Some(id) = self.selected_unit_id {
+            let path = match self.pathfinder.path(pos) {
+                Some(path) => path,
+                None => return,
+            };
+            let command_move = command::Command::MoveTo(command::MoveTo { id, path });
+            if check(&self.state, &command_move).is_err() {
+                return;
+            }
+            self.do_command(context, &command_move);
+            self.view.hide_path_preview();
+            if self.state.parts().agent.get_opt(id).is_some() {
+                self.pathfinder.fill_map(&self.state, id);
+            }
+        }
+    }
+
+    fn attack(&mut self, context: &mut Context, attacker_id: ObjId, target_id: ObjId) {
+        let command_attack = command::Command::Attack(command::Attack {
+            attacker_id,
+            target_id,
+        });
+        if check(&self.state, &command_attack).is_err() {
+            return;
+        }
+        self.do_command(context, &command_attack);
+        let parts = self.state.parts();
+        if parts.agent.get_opt(attacker_id).is_some() {
+            self.pathfinder.fill_map(&self.state, attacker_id);
         }
     }
 
     fn update_block_timer(&mut self, context: &mut Context, dtime: Time) {
+        let dtime = Time(dtime.0 * self.view.animation_speed());
         self.block_timer.as_mut().map(|t| t.0 -= dtime.0);
         if let Some(time) = self.block_timer {
             if time <= Time(0.0) {
@@ -364,6 +535,11 @@ impl Screen for Game {
         match event {
             Event::Click { pos } => self.handle_event_click(context, pos),
             Event::Resize { aspect_ratio } => self.gui.resize(aspect_ratio),
+            Event::PointerMove { pos } => self.handle_event_pointer_move(context, pos),
+            Event::Drag { delta, .. } => self.view.pan(delta),
+            Event::Scroll { delta } => self.view.zoom(delta),
+            Event::KeyDown { key } => self.handle_event_key_down(context, key),
+            _ => {}
         }
         self.handle_commands(context);
     }
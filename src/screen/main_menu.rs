@@ -7,6 +7,7 @@ use screen;
 enum Command {
     Exit,
     Start,
+    Editor,
 }
 
 #[derive(Debug)]
@@ -21,14 +22,17 @@ impl MainMenu {
         {
             let sprite_exit = gui::text_sprite(context, "exit", 0.1);
             let sprite_start = gui::text_sprite(context, "start", 0.1);
+            let sprite_editor = gui::text_sprite(context, "editor", 0.1);
             let button_id_exit = gui.add_button(context, sprite_exit, Command::Exit);
             let button_id_start = gui.add_button(context, sprite_start, Command::Start);
+            let button_id_editor = gui.add_button(context, sprite_editor, Command::Editor);
             let anchor = gui::Anchor {
                 vertical: gui::VAnchor::Middle,
                 horizontal: gui::HAnchor::Middle,
             };
             let direction = gui::Direction::Up;
-            let _ = gui.add_layout(anchor, direction, vec![button_id_exit, button_id_start]);
+            let buttons = vec![button_id_exit, button_id_editor, button_id_start];
+            let _ = gui.add_layout(anchor, direction, buttons);
         }
         let mut sprite_imp = Sprite::from_path(context, "imp.png", 2.0);
         sprite_imp.set_color([0.0, 0.0, 1.0, 0.2]);
@@ -43,6 +47,11 @@ impl MainMenu {
         context.add_command(hate::screen::Command::Push(game_screen));
     }
 
+    fn start_editor(&mut self, context: &mut Context) {
+        let editor_screen = Box::new(screen::Editor::new(context));
+        context.add_command(hate::screen::Command::Push(editor_screen));
+    }
+
     fn exit(&mut self, context: &mut Context) {
         context.add_command(hate::screen::Command::Pop);
     }
@@ -52,6 +61,7 @@ impl MainMenu {
         while let Some(command) = self.gui.try_recv() {
             match command {
                 Command::Start => self.start_new_game(context),
+                Command::Editor => self.start_editor(context),
                 Command::Exit => self.exit(context),
             }
         }
@@ -73,6 +83,7 @@ impl Screen for MainMenu {
             Event::Resize { aspect_ratio } => {
                 self.gui.resize(aspect_ratio);
             }
+            _ => {}
         }
     }
 }
@@ -1,5 +1,7 @@
 mod main_menu;
 mod game;
+mod editor;
 
 pub use self::main_menu::MainMenu;
 pub use self::game::Game;
+pub use self::editor::Editor;
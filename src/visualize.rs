@@ -1,8 +1,9 @@
+use std::f32::consts::PI;
 use cgmath::vec2;
-use hate::{Context, Sprite, Time};
+use hate::{AnimationMode, Context, Sound, Sprite, SpriteSheet, Time};
 use hate::scene::Action;
-use hate::scene::action;
-use hate::geom::Point;
+use hate::scene::action::{self, Easing};
+use hate::geom::{Point, Size};
 use hate::gui;
 use core::{ObjId, PlayerId, State};
 use core::event::{ActiveEvent, Event};
@@ -10,9 +11,40 @@ use core::map::PosHex;
 use core::event;
 use core::effect::{self, Effect};
 use core::execute::Phase;
-use game_view::GameView;
+use game_view::{self, GameView};
 use map;
 
+fn fork<A: Action + 'static>(action: A) -> Box<Action> {
+    Box::new(action::Fork::new(Box::new(action)))
+}
+
+fn push_sound(actions: &mut Vec<Box<Action>>, context: &Context, sound: &Option<Sound>) {
+    if let Some(ref sound) = *sound {
+        actions.push(Box::new(action::PlaySound::new(context.audio(), sound)));
+    }
+}
+
+/// `None` if the object's sprite sheet has no such animation
+fn new_animate(view: &mut GameView, id: ObjId, name: &str) -> Option<action::Animate> {
+    let sprite = view.id_to_sprite(id).clone();
+    let sheet = view.sprite_sheet(id)?;
+    if !sheet.has_animation(name) {
+        return None;
+    }
+    Some(action::Animate::new(&sprite, sheet, name))
+}
+
+fn animate(view: &mut GameView, id: ObjId, name: &str) -> Option<Box<Action>> {
+    new_animate(view, id, name).map(|action| Box::new(action) as Box<Action>)
+}
+
+/// The idle animation loops in the background until some other animation replaces it
+fn push_idle(actions: &mut Vec<Box<Action>>, view: &mut GameView, id: ObjId) {
+    if let Some(action_idle) = animate(view, id, "idle") {
+        actions.push(Box::new(action::Fork::new(action_idle)));
+    }
+}
+
 fn message(view: &mut GameView, context: &mut Context, pos: PosHex, text: &str) -> Box<Action> {
     let visible = [0.0, 0.0, 0.0, 1.0];
     let invisible = [0.0, 0.0, 0.0, 0.0];
@@ -21,17 +53,20 @@ fn message(view: &mut GameView, context: &mut Context, pos: PosHex, text: &str)
     let point = Point(point.0 + vec2(0.0, view.tile_size()));
     sprite.set_pos(point);
     sprite.set_color(invisible);
+    let action_fade_in = action::ChangeColorTo::new(&sprite, visible, Time(0.3));
+    // TODO: read the time from Config:
+    let action_fade_out = action::ChangeColorTo::new(&sprite, invisible, Time(1.0));
     let action_show_hide = Box::new(action::Sequence::new(vec![
         Box::new(action::Show::new(&view.layers().text, &sprite)),
-        Box::new(action::ChangeColorTo::new(&sprite, visible, Time(0.3))),
+        Box::new(action_fade_in.with_easing(Easing::QuadOut)),
         Box::new(action::Sleep::new(Time(1.0))),
-        // TODO: read the time from Config:
-        Box::new(action::ChangeColorTo::new(&sprite, invisible, Time(1.0))),
+        Box::new(action_fade_out.with_easing(Easing::QuadIn)),
         Box::new(action::Hide::new(&view.layers().text, &sprite)),
     ]));
     let time = action_show_hide.duration();
     let delta = Point(vec2(0.0, 0.3));
-    let action_move = Box::new(action::MoveBy::new(&sprite, delta, time));
+    let action_move = action::MoveBy::new(&sprite, delta, time).with_easing(Easing::CubicOut);
+    let action_move = Box::new(action_move);
     Box::new(action::Fork::new(Box::new(action::Sequence::new(vec![
         Box::new(action::Fork::new(action_move)),
         action_show_hide,
@@ -74,21 +109,23 @@ fn generate_brief_obj_info(
     id: ObjId,
 ) -> Box<Action> {
     let mut actions: Vec<Box<Action>> = Vec::new();
-    let agent = state.parts().agent.get(id);
-    let obj_pos = state.parts().pos.get(id).0;
-    let strength = state.parts().strength.get(id);
+    let parts = state.parts();
+    let obj_pos = parts.pos.get(id).0;
+    let strength = parts.strength.get(id);
+    let mut rows = vec![([0.0, 0.4, 0.0, 1.0], strength.strength.0)];
+    // Destructible objects don't have an `Agent` component - show only the strength.
+    if let Some(agent) = parts.agent.get_opt(id) {
+        rows.push(([1.0, 0.1, 1.0, 1.0], agent.jokers.0));
+        rows.push(([1.0, 0.0, 0.0, 1.0], agent.attacks.0));
+        rows.push(([0.0, 0.0, 1.0, 1.0], agent.moves.0));
+    }
     let size = 0.2 * view.tile_size();
     let mut point = map::hex_to_point(view.tile_size(), obj_pos);
     point.0.x += view.tile_size() * 0.8;
     point.0.y += view.tile_size() * 0.6;
     let mut dots = Vec::new();
     let base_x = point.0.x;
-    for &(color, n) in &[
-        ([0.0, 0.4, 0.0, 1.0], strength.strength.0),
-        ([1.0, 0.1, 1.0, 1.0], agent.jokers.0),
-        ([1.0, 0.0, 0.0, 1.0], agent.attacks.0),
-        ([0.0, 0.0, 1.0, 1.0], agent.moves.0),
-    ] {
+    for &(color, n) in &rows {
         for _ in 0..n {
             dots.push((color, point));
             point.0.x -= size;
@@ -122,7 +159,7 @@ fn showhide_brief_unit_info(
     if view.unit_info_check(id) {
         actions.push(remove_brief_unit_info(view, id));
     }
-    if state.parts().agent.get_opt(id).is_some() {
+    if state.parts().strength.get_opt(id).is_some() {
         actions.push(generate_brief_obj_info(state, view, context, id));
     }
     Box::new(action::Sequence::new(actions))
@@ -157,17 +194,29 @@ fn visualize_pre(
     Box::new(action::Sequence::new(actions))
 }
 
+/// Objects whose brief info dots may be outdated
+fn changed_obj_ids(state: &State) -> Vec<ObjId> {
+    let parts = state.parts();
+    let mut ids = Vec::new();
+    let changes = [parts.strength.changes(), parts.agent.changes()];
+    for changes in changes.iter().filter_map(|changes| changes.as_ref()) {
+        for id in changes.ids() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
 fn visualize_post(
     state: &State,
     view: &mut GameView,
     context: &mut Context,
-    event: &Event,
+    _: &Event,
 ) -> Box<Action> {
     let mut actions = Vec::new();
-    for &id in &event.actor_ids {
-        actions.push(showhide_brief_unit_info(state, view, context, id));
-    }
-    for &id in event.effects.keys() {
+    for id in changed_obj_ids(state) {
         actions.push(showhide_brief_unit_info(state, view, context, id));
     }
     Box::new(action::Sequence::new(actions))
@@ -189,33 +238,30 @@ fn visualize_event(
 }
 
 fn visualize_event_create(
-    _: &State,
+    state: &State,
     view: &mut GameView,
     context: &mut Context,
     event: &event::Create,
 ) -> Box<Action> {
     let point = map::hex_to_point(view.tile_size(), event.pos);
-    // TODO: Move to some .ron config:
-    let sprite_name = match event.prototype.as_str() {
-        "swordsman" => "swordsman.png",
-        "spearman" => "spearman.png",
-        "imp" => "imp.png",
-        "boulder" => "boulder.png",
-        _ => unimplemented!(),
-    };
+    let sprite_name = game_view::object_texture_name(state.prototypes(), &event.prototype);
     let size = view.tile_size() * 2.0;
-    let mut sprite = Sprite::from_path(context, sprite_name, size);
+    let sheet = SpriteSheet::from_path(context, sprite_name, size);
+    let mut sprite = Sprite::from_sprite_sheet(&sheet);
     sprite.set_color([1.0, 1.0, 1.0, 0.0]);
     sprite.set_pos(point);
     view.add_object(event.id, &sprite);
-    Box::new(action::Sequence::new(vec![
+    view.set_sprite_sheet(event.id, sheet);
+    let mut actions: Vec<Box<Action>> = vec![
         Box::new(action::Show::new(&view.layers().units, &sprite)),
         Box::new(action::ChangeColorTo::new(
             &sprite,
             [1.0, 1.0, 1.0, 1.0],
             Time(0.25),
         )),
-    ]))
+    ];
+    push_idle(&mut actions, view, event.id);
+    Box::new(action::Sequence::new(actions))
 }
 
 fn visualize_event_move_to(
@@ -225,13 +271,26 @@ fn visualize_event_move_to(
     event: &event::MoveTo,
 ) -> Box<Action> {
     let sprite = view.id_to_sprite(event.id).clone();
-    let mut actions: Vec<Box<Action>> = Vec::new();
+    let mut moves: Vec<Box<Action>> = Vec::new();
     for step in event.path.steps() {
         let from = map::hex_to_point(view.tile_size(), step.from);
         let to = map::hex_to_point(view.tile_size(), step.to);
         let diff = Point(to.0 - from.0);
-        actions.push(Box::new(action::MoveBy::new(&sprite, diff, Time(0.3))));
+        let action_move = action::MoveBy::new(&sprite, diff, Time(0.3));
+        moves.push(Box::new(action_move.with_easing(Easing::QuadInOut)));
+    }
+    let action_moves: Box<Action> = Box::new(action::Sequence::new(moves));
+    let mut actions: Vec<Box<Action>> = Vec::new();
+    match new_animate(view, event.id, "walk") {
+        // The walk animation stops when the unit arrives. It's always looped:
+        // a `Once` animation would win the race and stop the unit halfway.
+        Some(action_walk) => actions.push(Box::new(action::Race::new(vec![
+            Box::new(action_walk.with_mode(AnimationMode::Loop)),
+            action_moves,
+        ]))),
+        None => actions.push(action_moves),
     }
+    push_idle(&mut actions, view, event.id);
     Box::new(action::Sequence::new(actions))
 }
 
@@ -253,11 +312,21 @@ fn visualize_event_attack(
         actions.push(Box::new(action::Sleep::new(Time(0.3)))); // TODO: ??
         actions.push(message(view, context, map_from, "reaction"));
     }
-    actions.push(Box::new(action::MoveBy::new(&sprite, diff, Time(0.15))));
-    actions.push(Box::new(
-        action::MoveBy::new(&sprite, Point(-diff.0), Time(0.15)),
-    ));
+    // Lean towards the target during the lunge
+    let tilt = if diff.0.x > 0.0 { -0.2 } else { 0.2 };
+    let rotation = sprite.rotation();
+    if let Some(action_attack) = animate(view, event.attacker_id, "attack") {
+        actions.push(Box::new(action::Fork::new(action_attack)));
+    }
+    actions.push(fork(action::RotateTo::new(&sprite, rotation + tilt, Time(0.15))));
+    push_sound(&mut actions, context, &view.sounds().attack);
+    let action_lunge = action::MoveBy::new(&sprite, diff, Time(0.15));
+    actions.push(Box::new(action_lunge.with_easing(Easing::QuadIn)));
+    actions.push(fork(action::RotateTo::new(&sprite, rotation, Time(0.15))));
+    let action_return = action::MoveBy::new(&sprite, Point(-diff.0), Time(0.15));
+    actions.push(Box::new(action_return.with_easing(Easing::QuadOut)));
     actions.push(Box::new(action::Sleep::new(Time(0.1)))); // TODO: ??
+    push_idle(&mut actions, view, event.attacker_id);
     Box::new(action::Sequence::new(actions))
 }
 
@@ -286,13 +355,14 @@ fn visualize_event_begin_turn(
     let mut sprite = gui::text_sprite(context, text, 0.2);
     sprite.set_pos(Point(vec2(0.0, 0.0)));
     sprite.set_color(invisible);
-    Box::new(action::Sequence::new(vec![
-        Box::new(action::Show::new(&view.layers().text, &sprite)),
-        Box::new(action::ChangeColorTo::new(&sprite, visible, Time(0.2))),
-        Box::new(action::Sleep::new(Time(1.5))),
-        Box::new(action::ChangeColorTo::new(&sprite, invisible, Time(0.3))),
-        Box::new(action::Hide::new(&view.layers().text, &sprite)),
-    ]))
+    let mut actions: Vec<Box<Action>> = Vec::new();
+    push_sound(&mut actions, context, &view.sounds().turn);
+    actions.push(Box::new(action::Show::new(&view.layers().text, &sprite)));
+    actions.push(Box::new(action::ChangeColorTo::new(&sprite, visible, Time(0.2))));
+    actions.push(Box::new(action::Sleep::new(Time(1.5))));
+    actions.push(Box::new(action::ChangeColorTo::new(&sprite, invisible, Time(0.3))));
+    actions.push(Box::new(action::Hide::new(&view.layers().text, &sprite)));
+    Box::new(action::Sequence::new(actions))
 }
 
 fn visualize_effect(
@@ -319,17 +389,30 @@ fn visualize_effect_kill(
 ) -> Box<Action> {
     let pos = state.parts().pos.get(target_id).0;
     let sprite = view.id_to_sprite(target_id).clone();
+    let action_death = animate(view, target_id, "death");
     view.remove_object(target_id);
     let dark = [0.1, 0.1, 0.1, 1.0];
     let invisible = [0.1, 0.1, 0.1, 0.0];
-    Box::new(action::Sequence::new(vec![
-        message(view, context, pos, "killed"),
-        Box::new(action::Sleep::new(Time(0.25))),
-        show_blood_spot(view, context, pos),
-        Box::new(action::ChangeColorTo::new(&sprite, dark, Time(0.2))),
-        Box::new(action::ChangeColorTo::new(&sprite, invisible, Time(0.2))),
-        Box::new(action::Hide::new(&view.layers().units, &sprite)),
-    ]))
+    let is_agent = state.parts().agent.get_opt(target_id).is_some();
+    let text = if is_agent { "killed" } else { "destroyed" };
+    let mut actions: Vec<Box<Action>> = Vec::new();
+    push_sound(&mut actions, context, &view.sounds().kill);
+    actions.push(message(view, context, pos, text));
+    actions.push(Box::new(action::Sleep::new(Time(0.25))));
+    if is_agent {
+        actions.push(show_blood_spot(view, context, pos));
+    }
+    match action_death {
+        Some(action_death) => actions.push(action_death),
+        None => {
+            actions.push(Box::new(action::StopAnimation::new(&sprite)));
+            actions.push(fork(action::RotateBy::new(&sprite, PI * 2.0, Time(0.4))));
+        }
+    }
+    actions.push(Box::new(action::ChangeColorTo::new(&sprite, dark, Time(0.2))));
+    actions.push(Box::new(action::ChangeColorTo::new(&sprite, invisible, Time(0.2))));
+    actions.push(Box::new(action::Hide::new(&view.layers().units, &sprite)));
+    Box::new(action::Sequence::new(actions))
 }
 
 fn visualize_effect_wound(
@@ -344,12 +427,27 @@ fn visualize_effect_wound(
     let sprite = view.id_to_sprite(target_id).clone();
     let color_normal = sprite.color();
     let color_dark = [0.1, 0.1, 0.1, 1.0];
-    Box::new(action::Sequence::new(vec![
-        message(view, context, pos, &format!("wounded - {}", damage.0)),
+    let is_agent = state.parts().agent.get_opt(target_id).is_some();
+    let text = if is_agent { "wounded" } else { "damaged" };
+    let size = sprite.size();
+    let size_squashed = Size {
+        w: size.w * 1.2,
+        h: size.h * 0.8,
+    };
+    let action_squash = action::Sequence::new(vec![
+        Box::new(action::ChangeSizeTo::new(&sprite, size_squashed, Time(0.1))),
+        Box::new(action::ChangeSizeTo::new(&sprite, size, Time(0.2))),
+    ]);
+    let mut actions: Vec<Box<Action>> = vec![
+        message(view, context, pos, &format!("{} - {}", text, damage.0)),
+        fork(action_squash),
         Box::new(action::ChangeColorTo::new(&sprite, color_dark, Time(0.2))),
         Box::new(action::ChangeColorTo::new(&sprite, color_normal, Time(0.2))),
-        show_blood_spot(view, context, pos),
-    ]))
+    ];
+    if is_agent {
+        actions.push(show_blood_spot(view, context, pos));
+    }
+    Box::new(action::Sequence::new(actions))
 }
 
 fn visualize_effect_miss(
@@ -359,5 +457,8 @@ fn visualize_effect_miss(
     target_id: ObjId,
 ) -> Box<Action> {
     let pos = state.parts().pos.get(target_id).0;
-    message(view, context, pos, "missed")
+    let mut actions: Vec<Box<Action>> = Vec::new();
+    push_sound(&mut actions, context, &view.sounds().miss);
+    actions.push(message(view, context, pos, "missed"));
+    Box::new(action::Sequence::new(actions))
 }